@@ -3,7 +3,14 @@ extern crate test;
 extern crate chess_algorithm;
 
 use test::Bencher;
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color::WHITE;
 use chess_algorithm::figure_list::*;
+use chess_algorithm::perft::perft;
+
+const MIDDLE_GAME: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w - - 0 1";
 
 #[bench]
 fn bench_array_iterations(b: &mut Bencher) {
@@ -21,16 +28,65 @@ fn bench_array_iterations(b: &mut Bencher) {
 }
 
 #[bench]
-fn bench_node_iterations(b: &mut Bencher) {
-    let mut nodes = [PointLinkedNode::new(); 18];
-
-    for i in (0..nodes.len() - 2).step_by(2) {
-        nodes[i].next = &mut nodes[i+2];
-    }
+fn bench_point_list_iterations(b: &mut Bencher) {
+    let board = ByteBoard::default();
+    let list = FigurePointList::new(&board, WHITE);
 
     let mut count = 0;
     b.iter(|| {
-        count += nodes.iter().count()
+        count += list.iter().map(|p| p.x() as usize).sum::<usize>()
     });
     println!("{}", count)
-}
\ No newline at end of file
+}
+
+fn make_unmake<L: PieceList>(b: &mut Bencher) {
+    let (board, color) = fen::parse(MIDDLE_GAME).unwrap();
+    let mut holder: BoardDataHolder<ByteBoard, L> = BoardDataHolder::with_lists(&board);
+    let mut controller = holder.controller(color);
+    let movies = controller.friend_movies();
+
+    b.iter(|| {
+        for movement in movies.iter() {
+            let move_info = controller.make_move(movement);
+            controller.unmake_move(move_info);
+        }
+    });
+}
+
+fn list_perft<L: PieceList>(b: &mut Bencher) {
+    let (board, color) = fen::parse(MIDDLE_GAME).unwrap();
+    let mut holder: BoardDataHolder<ByteBoard, L> = BoardDataHolder::with_lists(&board);
+    let mut controller = holder.controller(color);
+
+    b.iter(|| perft(&mut controller, 2));
+}
+
+#[bench]
+fn bench_make_unmake(b: &mut Bencher) {
+    make_unmake::<FigurePointList>(b)
+}
+
+#[bench]
+fn bench_array_list_make_unmake(b: &mut Bencher) {
+    make_unmake::<FigureArrayList>(b)
+}
+
+#[bench]
+fn bench_linked_list_make_unmake(b: &mut Bencher) {
+    make_unmake::<FigureLinkedList>(b)
+}
+
+#[bench]
+fn bench_point_list_perft(b: &mut Bencher) {
+    list_perft::<FigurePointList>(b)
+}
+
+#[bench]
+fn bench_array_list_perft(b: &mut Bencher) {
+    list_perft::<FigureArrayList>(b)
+}
+
+#[bench]
+fn bench_linked_list_perft(b: &mut Bencher) {
+    list_perft::<FigureLinkedList>(b)
+}
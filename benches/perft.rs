@@ -0,0 +1,42 @@
+//! Perft speed of the board representations: `cargo +nightly bench --bench perft`.
+
+#![feature(test)]
+
+extern crate test;
+
+use chess_algorithm::bitboard::BitBoard;
+use chess_algorithm::board::{Board, ByteBoard};
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color;
+use chess_algorithm::perft::perft;
+use test::Bencher;
+
+const MIDDLE_GAME: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w - - 0 1";
+
+fn bench_perft<B: Board>(bencher: &mut Bencher, board: &B, color: Color, depth: u32) {
+    let mut holder = BoardDataHolder::new(board);
+    bencher.iter(|| perft(&mut holder.controller(color), depth));
+}
+
+#[bench]
+fn start_position_byte_board(bencher: &mut Bencher) {
+    bench_perft(bencher, &ByteBoard::default(), Color::WHITE, 3);
+}
+
+#[bench]
+fn start_position_bit_board(bencher: &mut Bencher) {
+    bench_perft(bencher, &BitBoard::default(), Color::WHITE, 3);
+}
+
+#[bench]
+fn middle_game_byte_board(bencher: &mut Bencher) {
+    let (board, color) = fen::parse(MIDDLE_GAME).unwrap();
+    bench_perft(bencher, &board, color, 2);
+}
+
+#[bench]
+fn middle_game_bit_board(bencher: &mut Bencher) {
+    let (board, color) = fen::parse(MIDDLE_GAME).unwrap();
+    bench_perft(bencher, &board.to_board::<BitBoard>(), color, 2);
+}
@@ -7,6 +7,7 @@ use test::Bencher;
 use chess_algorithm::board::ByteBoard;
 use chess_algorithm::board_controller::*;
 use chess_algorithm::figure::Color::*;
+use chess_algorithm::lazy_smp::LazySmpSearch;
 use chess_algorithm::score::{AlphaBetaSearch, MinMaxSimpleSearch, MoveSearch};
 
 #[bench]
@@ -25,4 +26,28 @@ fn bench_min_max_simple(b: &mut Bencher) {
         let mut controller = board_data_holder.controller(WHITE);
         MinMaxSimpleSearch::default().find_best_move(&mut controller, 4);
     });
-}
\ No newline at end of file
+}
+
+fn bench_lazy_smp(b: &mut Bencher, threads: usize) {
+    let mut board_data_holder = BoardDataHolder::new(&ByteBoard::default());
+    let search = LazySmpSearch::new(threads);
+    b.iter(|| {
+        let mut controller = board_data_holder.controller(WHITE);
+        search.find_best_move(&mut controller, 5);
+    });
+}
+
+#[bench]
+fn bench_lazy_smp_1_thread(b: &mut Bencher) {
+    bench_lazy_smp(b, 1);
+}
+
+#[bench]
+fn bench_lazy_smp_2_threads(b: &mut Bencher) {
+    bench_lazy_smp(b, 2);
+}
+
+#[bench]
+fn bench_lazy_smp_4_threads(b: &mut Bencher) {
+    bench_lazy_smp(b, 4);
+}
@@ -0,0 +1,32 @@
+use std::env;
+use std::process;
+
+use chess_algorithm::book_builder::{BookBuilder, DEFAULT_BOOK_PATH, DEFAULT_MAX_PLIES};
+use chess_algorithm::database::{DataBaseInstance, DEFAULT_PATH};
+
+fn usage() -> ! {
+    println!("Usage: book_build [database] [output file] [max plies] [min count]");
+    println!();
+    println!("Builds a Polyglot opening book from the games the console game stored in the database,");
+    println!("counting the first plies of every game, movies played less than min count times are left out.");
+    println!("Defaults are {}, {}, {} plies and 1.", DEFAULT_PATH, DEFAULT_BOOK_PATH, DEFAULT_MAX_PLIES);
+    process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") { usage() }
+
+    let database = args.first().map_or(DEFAULT_PATH, String::as_str);
+    let output = args.get(1).map_or(DEFAULT_BOOK_PATH, String::as_str);
+    let max_plies = args.get(2).map_or(Ok(DEFAULT_MAX_PLIES), |a| a.parse()).unwrap_or_else(|_| usage());
+    let min_count = args.get(3).map_or(Ok(1), |a| a.parse()).unwrap_or_else(|_| usage());
+
+    let db = DataBaseInstance::new(database).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+    let mut builder = BookBuilder::new(max_plies);
+    let added = builder.add_database(&db).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+
+    let book = builder.build(min_count);
+    println!("{} games, {} positions, {} book entries", added, builder.positions(), book.len());
+    book.save(output).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+}
@@ -0,0 +1,43 @@
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use chess_algorithm::tablebase::{Tablebase, Wdl, DEFAULT_SIGNATURES, DEFAULT_TABLEBASE_DIR, MAX_MEN};
+
+fn usage() -> ! {
+    println!("Usage: tablebase_gen [output directory] [signatures...]");
+    println!();
+    println!("Signatures list the figures of both sides starting with their kings, like KQK or KRKP,");
+    println!("up to {} figures in total. Tables the given ones depend on are generated as well.", MAX_MEN);
+    println!("Defaults are {} and {}.", DEFAULT_TABLEBASE_DIR, DEFAULT_SIGNATURES.join(" "));
+    process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") { usage() }
+
+    let output = args.first().map_or(DEFAULT_TABLEBASE_DIR, String::as_str);
+    let signatures: Vec<&str> = if args.len() > 1 {
+        args[1..].iter().map(String::as_str).collect()
+    } else {
+        DEFAULT_SIGNATURES.to_vec()
+    };
+
+    let mut tablebase = Tablebase::load_dir(output).unwrap_or_default();
+    for signature in signatures {
+        let timer = Instant::now();
+        if tablebase.generate(signature).is_err() {
+            println!("Malformed signature {}", signature);
+            usage()
+        }
+        println!("{} generated in {:.1} sec", signature.to_uppercase(), timer.elapsed().as_secs_f32());
+    }
+
+    for signature in tablebase.signatures() {
+        let table = tablebase.table(signature).unwrap();
+        println!("{}: {} wins, {} draws, {} losses, longest mate {} plies",
+                 signature, table.count(Wdl::WIN), table.count(Wdl::DRAW), table.count(Wdl::LOSS), table.longest_mate());
+    }
+    tablebase.save_dir(output).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+}
@@ -0,0 +1,54 @@
+use std::env;
+use std::process;
+
+use chess_algorithm::database::{DataBaseInstance, DEFAULT_PATH};
+use chess_algorithm::eval_params::EvalParams;
+use chess_algorithm::tuning::{load_positions, positions_from_database, TexelTuner};
+
+fn usage() -> ! {
+    println!("Usage: texel_tune <positions file | --database <path>> <output file> [passes]");
+    println!();
+    println!("Positions file lines are '<fen>; <result>' or '<fen> [<result>]',");
+    println!("results are 1-0, 0-1, 1/2-1/2 or a number from 0 to 1.");
+    println!("The database is the one the console game writes, {} by default.", DEFAULT_PATH);
+    println!("The output file is written after every pass and can be loaded by the engine.");
+    process::exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (positions, rest) = match args.first().map(String::as_str) {
+        None => usage(),
+        Some("--database") => {
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let db = DataBaseInstance::new(path).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+            (positions_from_database(&db).map_err(|e| e.to_string()), &args[2..])
+        }
+        Some(path) => (load_positions(path).map_err(|e| e.to_string()), &args[1..]),
+    };
+    let positions = positions.unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+
+    let output = rest.first().unwrap_or_else(|| usage());
+    let passes = match rest.get(1).map(|p| p.parse::<usize>()) {
+        None => 100,
+        Some(Ok(passes)) => passes,
+        Some(Err(_)) => usage(),
+    };
+    if positions.is_empty() {
+        println!("No positions to tune on");
+        process::exit(1)
+    }
+
+    let mut tuner = TexelTuner::new(&positions);
+    let mut params = EvalParams::default();
+    println!("{} positions, k = {:.3}, error {:.6}", tuner.len(), tuner.fit_k(&params), tuner.error(&params));
+
+    for pass in 1..=passes {
+        let tuned = tuner.tune(&params, 1);
+        let done = tuned == params;
+        params = tuned;
+        params.save(output).unwrap_or_else(|e| { println!("{}", e); process::exit(1) });
+        println!("pass {}: error {:.6}", pass, tuner.error(&params));
+        if done { break }
+    }
+}
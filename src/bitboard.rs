@@ -0,0 +1,337 @@
+//! Bitboard position representation.
+//!
+//! `BitBoard` keeps a `u64` occupancy mask per color and per rank next to a plain array of the
+//! 64 figures, the array answers `Board::point` and the masks give attacks without walking the
+//! board: knights, kings and pawns through precomputed tables, sliders through magic bitboards.
+//!
+//! Bit `y * 8 + x` stands for `Point::new(x, y)`, so bit 0 is H1 and bit 63 is A8. Magic numbers
+//! are found once per process by a seeded random search, no PEXT instructions are needed.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use crate::board::{Board, ByteBoard};
+use crate::figure::{Color, Figure, Rank};
+use crate::point::Point;
+
+const KING_STEPS: [(i8, i8); 8] = [(0, 1), (1, 0), (1, 1), (0, -1), (-1, 0), (-1, -1), (-1, 1), (1, -1)];
+const KNIGHT_STEPS: [(i8, i8); 8] = [(1, 2), (2, 1), (-1, 2), (2, -1), (1, -2), (-2, 1), (-1, -2), (-2, -1)];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (-1, 0), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+const MAGIC_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// Bit index of `point` in the masks.
+#[inline]
+pub fn square(point: Point) -> usize {
+    (point.y() * 8 + point.x()) as usize
+}
+
+#[inline]
+pub fn square_point(square: usize) -> Point {
+    Point::new((square % 8) as i8, (square / 8) as i8)
+}
+
+fn is_inside(x: i8, y: i8) -> bool {
+    (0..8).contains(&x) && (0..8).contains(&y)
+}
+
+fn step_mask(square: usize, steps: &[(i8, i8)]) -> u64 {
+    let p = square_point(square);
+    steps.iter()
+        .map(|(dx, dy)| (p.x() + dx, p.y() + dy))
+        .filter(|(x, y)| is_inside(*x, *y))
+        .fold(0, |mask, (x, y)| mask | 1 << (y * 8 + x))
+}
+
+/// Slider attacks walking the rays, used to fill the magic tables.
+fn ray_attacks(square: usize, occupied: u64, directions: &[(i8, i8)]) -> u64 {
+    let p = square_point(square);
+    let mut attacks = 0;
+    for (dx, dy) in directions {
+        let (mut x, mut y) = (p.x() + dx, p.y() + dy);
+        while is_inside(x, y) {
+            let bit = 1u64 << (y * 8 + x);
+            attacks |= bit;
+            if occupied & bit != 0 { break }
+            x += dx;
+            y += dy;
+        }
+    }
+    attacks
+}
+
+/// Cells whose occupancy matters for the slider on `square`: its rays without their last cell.
+fn relevant_mask(square: usize, directions: &[(i8, i8)]) -> u64 {
+    let p = square_point(square);
+    let mut mask = 0;
+    for (dx, dy) in directions {
+        let (mut x, mut y) = (p.x() + dx, p.y() + dy);
+        while is_inside(x + dx, y + dy) {
+            mask |= 1u64 << (y * 8 + x);
+            x += dx;
+            y += dy;
+        }
+    }
+    mask
+}
+
+#[derive(Default, Copy, Clone)]
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    #[inline]
+    fn index(&self, occupied: u64) -> usize {
+        self.offset + ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+struct AttackTables {
+    king: [u64; 64],
+    knight: [u64; 64],
+    /// White pawn attacks first, then black ones.
+    pawn: [[u64; 64]; 2],
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    sliders: Vec<u64>,
+}
+
+impl AttackTables {
+    fn new() -> Self {
+        let mut tables = AttackTables {
+            king: [0; 64],
+            knight: [0; 64],
+            pawn: [[0; 64]; 2],
+            rook: [Magic::default(); 64],
+            bishop: [Magic::default(); 64],
+            sliders: Vec::new(),
+        };
+        let mut random = MAGIC_SEED;
+        for square in 0..64 {
+            tables.king[square] = step_mask(square, &KING_STEPS);
+            tables.knight[square] = step_mask(square, &KNIGHT_STEPS);
+            tables.pawn[0][square] = step_mask(square, &[(1, 1), (-1, 1)]);
+            tables.pawn[1][square] = step_mask(square, &[(1, -1), (-1, -1)]);
+            tables.rook[square] = find_magic(square, &ROOK_DIRECTIONS, &mut tables.sliders, &mut random);
+            tables.bishop[square] = find_magic(square, &BISHOP_DIRECTIONS, &mut tables.sliders, &mut random);
+        }
+        tables
+    }
+}
+
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Searches a magic for the slider on `square` and appends its attack table to `sliders`.
+fn find_magic(square: usize, directions: &[(i8, i8)], sliders: &mut Vec<u64>, random: &mut u64) -> Magic {
+    let mask = relevant_mask(square, directions);
+    let bits = mask.count_ones();
+
+    // Every subset of the mask with its attacks, enumerated by the carry-rippler trick.
+    let mut occupancies = Vec::with_capacity(1 << bits);
+    let mut subset = 0u64;
+    loop {
+        occupancies.push((subset, ray_attacks(square, subset, directions)));
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 { break }
+    }
+
+    let offset = sliders.len();
+    let mut table = vec![0u64; 1 << bits];
+    let mut used = vec![0u32; 1 << bits];
+    for attempt in 1.. {
+        let magic = next_random(random) & next_random(random) & next_random(random);
+        if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 { continue }
+
+        let candidate = Magic { mask, magic, shift: 64 - bits, offset: 0 };
+        let fits = occupancies.iter().all(|(occupied, attacks)| {
+            let i = candidate.index(*occupied);
+            if used[i] != attempt {
+                used[i] = attempt;
+                table[i] = *attacks;
+                true
+            } else {
+                table[i] == *attacks
+            }
+        });
+        if fits {
+            sliders.extend_from_slice(&table);
+            return Magic { offset, ..candidate };
+        }
+    }
+    unreachable!()
+}
+
+static TABLES: LazyLock<AttackTables> = LazyLock::new(AttackTables::new);
+
+pub fn king_attacks(square: usize) -> u64 {
+    TABLES.king[square]
+}
+
+pub fn knight_attacks(square: usize) -> u64 {
+    TABLES.knight[square]
+}
+
+pub fn pawn_attacks(square: usize, color: Color) -> u64 {
+    TABLES.pawn[if color == Color::WHITE { 0 } else { 1 }][square]
+}
+
+pub fn rook_attacks(square: usize, occupied: u64) -> u64 {
+    TABLES.sliders[TABLES.rook[square].index(occupied)]
+}
+
+pub fn bishop_attacks(square: usize, occupied: u64) -> u64 {
+    TABLES.sliders[TABLES.bishop[square].index(occupied)]
+}
+
+fn color_index(color: Color) -> Option<usize> {
+    match color {
+        Color::WHITE => Some(0),
+        Color::BLACK => Some(1),
+        _ => None,
+    }
+}
+
+fn rank_index(rank: Rank) -> Option<usize> {
+    match rank {
+        Rank::NONE | Rank::OUT => None,
+        rank => Some(rank as usize - Rank::KING as usize),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BitBoard {
+    cells: [Figure; 64],
+    colors: [u64; 2],
+    ranks: [u64; 6],
+    /// Answer of `point` for the cells around the board.
+    out: Figure,
+}
+
+impl BitBoard {
+    pub fn empty() -> Self {
+        BitBoard {
+            cells: [Figure::empty(); 64],
+            colors: [0; 2],
+            ranks: [0; 6],
+            out: Figure::new(Rank::OUT, Color::NONE, false),
+        }
+    }
+
+    /// Cells occupied by any figure.
+    #[inline]
+    pub fn occupied(&self) -> u64 {
+        self.colors[0] | self.colors[1]
+    }
+
+    /// Cells occupied by figures of `color`.
+    pub fn color_mask(&self, color: Color) -> u64 {
+        color_index(color).map_or(0, |i| self.colors[i])
+    }
+
+    /// Cells occupied by figures of `rank` and `color`.
+    pub fn figure_mask(&self, rank: Rank, color: Color) -> u64 {
+        rank_index(rank).map_or(0, |i| self.ranks[i] & self.color_mask(color))
+    }
+
+    /// Cells attacked by the figure on `point`, see `movement::for_each_attack`.
+    pub fn attacks(&self, point: Point) -> u64 {
+        let figure = Board::point(self, point);
+        let square = square(point);
+        match figure.rank() {
+            Rank::KING => king_attacks(square),
+            Rank::KNIGHT => knight_attacks(square),
+            Rank::PAWN => pawn_attacks(square, figure.color()),
+            Rank::ROOK => rook_attacks(square, self.occupied()),
+            Rank::BISHOP => bishop_attacks(square, self.occupied()),
+            Rank::QUEEN => rook_attacks(square, self.occupied()) | bishop_attacks(square, self.occupied()),
+            Rank::NONE | Rank::OUT => 0,
+        }
+    }
+
+    /// `Board::attackers` from the attack tables: a figure attacks `point` when a figure of its rank
+    /// standing on `point` would attack it back, pawns looking the other way.
+    pub fn attackers_mask(&self, point: Point, color: Color) -> u64 {
+        if !is_inside(point.x(), point.y()) { return 0 }
+
+        let square = square(point);
+        let occupied = self.occupied();
+        let queens = self.figure_mask(Rank::QUEEN, color);
+        (king_attacks(square) & self.figure_mask(Rank::KING, color))
+            | (knight_attacks(square) & self.figure_mask(Rank::KNIGHT, color))
+            | (pawn_attacks(square, color.invert()) & self.figure_mask(Rank::PAWN, color))
+            | (rook_attacks(square, occupied) & (self.figure_mask(Rank::ROOK, color) | queens))
+            | (bishop_attacks(square, occupied) & (self.figure_mask(Rank::BISHOP, color) | queens))
+    }
+
+    fn toggle(&mut self, square: usize, figure: Figure) {
+        if let (Some(color), Some(rank)) = (color_index(figure.color()), rank_index(figure.rank())) {
+            self.colors[color] ^= 1 << square;
+            self.ranks[rank] ^= 1 << square;
+        }
+    }
+}
+
+impl Board for BitBoard {
+    fn empty() -> Self {
+        BitBoard::empty()
+    }
+
+    #[inline]
+    fn point(&self, point: Point) -> &Figure {
+        if is_inside(point.x(), point.y()) { &self.cells[square(point)] } else { &self.out }
+    }
+
+    #[inline]
+    fn set_point(&mut self, point: Point, figure: Figure) {
+        debug_assert!(is_inside(point.x(), point.y()));
+        let square = square(point);
+        self.toggle(square, self.cells[square]);
+        self.toggle(square, figure);
+        self.cells[square] = figure;
+    }
+
+    /// Cells file by file like `ByteBoard::cell_iter`.
+    fn cell_iter(&self) -> impl Iterator<Item = (Point, &Figure)> {
+        (0..8).flat_map(move |x| (0..8).map(move |y| {
+            let p = Point::new(x, y);
+            (p, &self.cells[square(p)])
+        }))
+    }
+
+    #[inline]
+    fn for_each_attack<F: FnMut(Point)>(&self, p: Point, mut f: F) {
+        let mut attacks = self.attacks(p);
+        while attacks != 0 {
+            f(square_point(attacks.trailing_zeros() as usize));
+            attacks &= attacks - 1;
+        }
+    }
+
+    #[inline]
+    fn attackers(&self, point: Point, color: Color) -> u64 {
+        self.attackers_mask(point, color)
+    }
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        ByteBoard::default().to_board()
+    }
+}
+
+impl Display for BitBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_board::<ByteBoard>())
+    }
+}
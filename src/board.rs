@@ -35,6 +35,15 @@
 //! - `swap(&mut self, p1: Point, p2: Point)`:
 //!   Swaps the figures at two specified `Point` locations.
 //!
+//! ## Transformations
+//! - `color_flipped(&self) -> Self`:
+//!   Returns the board with ranks mirrored and the colors of all figures swapped,
+//!   so white's position becomes black's. Evaluations should give the same score
+//!   for a board and its flipped copy with the other side to move.
+//!
+//! - `mirrored(&self) -> Self`:
+//!   Returns the board reflected left to right, file A becomes file H.
+//!
 //! ## Accessor Methods
 //! - `cell(&self, literal: isize, number: isize) -> &Figure`:
 //!   Returns a reference to the figure at a specific (literal, number) coordinate.
@@ -46,6 +55,11 @@
 //! - `cell_iter(&self) -> impl Iterator<Item = (Point, &Figure)>`:
 //!   Returns an iterator over the main 8x8 area of the board, yielding pairs of `Point` and `Figure` references.
 //!
+//! ## Board trait
+//! - `Board` is the cell level API shared with `BitBoard`: `point`, `set_point`, `swap`,
+//!   `cell_iter` and `for_each_attack`. `MoveGenerator`, `BoardController` and the evaluation
+//!   are generic over it and default to `ByteBoard`.
+//!
 //! ## Display
 //! - Implements the `Display` trait to format the board as a human-readable string, 
 //!   with rows labeled 1-8 and columns labeled A-H for the main 8x8 area.
@@ -67,6 +81,7 @@
 
 use crate::point::*;
 use crate::figure::*;
+use crate::movement::{walk_attackers, walk_attacks};
 use std::fmt;
 use std::fmt::{Display, Formatter, Debug};
 
@@ -128,6 +143,27 @@ impl ByteBoard {
         *self.point_mut(p2) = f;
     }
 
+    /// The same position seen from the other side: ranks are mirrored and every figure changes color.
+    pub fn color_flipped(&self) -> Self {
+        let mut board = ByteBoard::empty();
+        for (p, f) in self.cell_iter() {
+            *board.cell_mut(p.x() as isize, 7 - p.y() as isize) = match f.rank() {
+                Rank::NONE | Rank::OUT => *f,
+                rank => Figure::new(rank, f.color().invert(), f.is_flag_set()),
+            };
+        }
+        board
+    }
+
+    /// The board reflected left to right, file A becomes file H.
+    pub fn mirrored(&self) -> Self {
+        let mut board = ByteBoard::empty();
+        for (p, f) in self.cell_iter() {
+            *board.cell_mut(7 - p.x() as isize, p.y() as isize) = *f;
+        }
+        board
+    }
+
     pub fn cell_iter(&self) -> impl Iterator<Item = (Point, &Figure)> {
         self.cells[4..12].iter()
             .enumerate()
@@ -139,6 +175,79 @@ impl ByteBoard {
     }
 }
 
+/// Cell level access to a position, so that move generation, `BoardController` and the
+/// evaluation work with `ByteBoard` as well as with `BitBoard`.
+///
+/// Points outside of the main 8x8 area, up to four cells away from it, hold an `OUT` figure,
+/// movement rays stop at them.
+pub trait Board: Copy + PartialEq + Debug + Display + Default + Send + Sync + 'static {
+    /// Board without figures.
+    fn empty() -> Self;
+
+    fn point(&self, point: Point) -> &Figure;
+
+    /// Puts `figure` on `point` of the main 8x8 area.
+    fn set_point(&mut self, point: Point, figure: Figure);
+
+    fn swap(&mut self, p1: Point, p2: Point) {
+        let f = *self.point(p1);
+        self.set_point(p1, *self.point(p2));
+        self.set_point(p2, f);
+    }
+
+    /// Cells of the main 8x8 area with their figures.
+    fn cell_iter(&self) -> impl Iterator<Item = (Point, &Figure)>;
+
+    /// Calls `f` for every cell attacked by the figure standing on `p`, see
+    /// `movement::for_each_attack`. Walks the rays cell by cell unless the board knows better.
+    fn for_each_attack<F: FnMut(Point)>(&self, p: Point, f: F) {
+        walk_attacks(self, p, f)
+    }
+
+    /// Cells of the `color` figures attacking `point` as bits `y * 8 + x`, the layout of
+    /// `bitboard`, whatever stands on `point`.
+    fn attackers(&self, point: Point, color: Color) -> u64 {
+        walk_attackers(self, point, color)
+    }
+
+    fn is_square_attacked(&self, point: Point, color: Color) -> bool {
+        self.attackers(point, color) != 0
+    }
+
+    /// The same position in another representation.
+    fn to_board<T: Board>(&self) -> T {
+        let mut board = T::empty();
+        self.cell_iter()
+            .filter(|(_, f)| f.rank() != Rank::NONE)
+            .for_each(|(p, f)| board.set_point(p, *f));
+        board
+    }
+}
+
+impl Board for ByteBoard {
+    fn empty() -> Self {
+        ByteBoard::empty()
+    }
+
+    #[inline]
+    fn point(&self, point: Point) -> &Figure {
+        ByteBoard::point(self, point)
+    }
+
+    #[inline]
+    fn set_point(&mut self, point: Point, figure: Figure) {
+        *self.point_mut(point) = figure;
+    }
+
+    fn swap(&mut self, p1: Point, p2: Point) {
+        ByteBoard::swap(self, p1, p2)
+    }
+
+    fn cell_iter(&self) -> impl Iterator<Item = (Point, &Figure)> {
+        ByteBoard::cell_iter(self)
+    }
+}
+
 impl Default for ByteBoard {
     fn default() -> Self {
         let mut board = ByteBoard {
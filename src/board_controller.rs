@@ -1,64 +1,95 @@
-use crate::board::ByteBoard;
+use crate::board::{Board, ByteBoard};
 use crate::figure::Color::{BLACK, WHITE};
-use crate::figure::Rank::{KING, QUEEN};
+use crate::figure::Rank::{KING, NONE, PAWN};
 use crate::figure::{Color, Figure};
-use crate::figure_list::{FigurePointList, LinkedNodeCursor};
+use crate::figure_list::{FigurePointList, PieceList};
 use crate::movement::MoveType::SIMPLE;
 use crate::movement::{Move, MoveGenerator, MoveList, MoveType};
+use crate::nnue::Accumulator;
 use crate::point::Point;
+use crate::zobrist;
 
-pub struct BoardDataHolder {
+pub struct BoardDataHolder<B: Board = ByteBoard, L: PieceList = FigurePointList> {
     // TODO remove pub for preventing board changes
-    pub board: ByteBoard,
-    pub white_list: FigurePointList,
-    pub black_list: FigurePointList,
+    pub board: B,
+    pub white_list: L,
+    pub black_list: L,
+    turn: Color,
+    halfmove_clock: u32,
+    history: Vec<HistoryEntry>,
+    undone: Vec<Move>,
 }
 
-pub struct BoardController<'a> {
-    pub(crate) board: &'a mut ByteBoard,
-    pub(crate) friend_list: &'a mut FigurePointList,
-    pub(crate) enemy_list: &'a mut FigurePointList,
+pub struct BoardController<'a, B: Board = ByteBoard, L: PieceList = FigurePointList> {
+    pub(crate) board: &'a mut B,
+    pub(crate) friend_list: &'a mut L,
+    pub(crate) enemy_list: &'a mut L,
     pub(crate) friend_color: Color,
     pub(crate) enemy_color: Color,
+    pub(crate) accumulator: Option<Accumulator>,
     pub position_counter: i32
 }
 
-#[derive(Default)]
+/// A cell changed by `make_move` as it was before the move, which is all `unmake_move` needs.
+#[derive(Default, Debug, Copy, Clone)]
 pub struct PointInfo {
     pub figure: Figure,
     pub point: Point,
-    pub cursor: LinkedNodeCursor,
+}
+
+/// A move played by `BoardDataHolder::play` with what it takes to take it back.
+#[derive(Debug, Copy, Clone)]
+pub struct HistoryEntry {
+    /// The move with its captured figure.
+    pub movement: Move,
+    pub color: Color,
+    pub undo: (PointInfo, PointInfo),
+    /// Zobrist hash of the position the move was played in.
+    pub hash: u64,
+    /// Halfmove clock of the position the move was played in.
+    pub halfmove_clock: u32,
 }
 
 impl PointInfo {
-    pub fn new(point: &Point, board_controller: &mut BoardController) -> Self {
-        let mut info = PointInfo::default();
-        info.point = *point;
+    pub fn new<B: Board, L: PieceList>(point: &Point, board_controller: &BoardController<B, L>) -> Self {
+        PointInfo { figure: *board_controller.board.point(*point), point: *point }
+    }
+}
 
-        info.figure = *board_controller.board.point(*point);
+impl<'a, B: Board, L: PieceList> BoardController<'a, B, L> {
+    #[inline]
+    pub fn friend_color(&self) -> Color {
+        self.friend_color
+    }
 
-        let node_iter =
-            if info.figure.color() == board_controller.friend_color {
-                board_controller.friend_list.node_iter()
-            } else if info.figure.color() == board_controller.enemy_color {
-                board_controller.enemy_list.node_iter()
-            } else {
-                return info;
-            };
+    #[inline]
+    pub fn enemy_color(&self) -> Color {
+        self.enemy_color
+    }
 
-        info.cursor = node_iter
-            .skip_while(|lnc| lnc.point() != *point)
-            .next()
-            .expect("Point should be in list");
+    #[inline]
+    pub fn board(&self) -> &B {
+        self.board
+    }
 
-        return info;
+    #[inline]
+    pub fn friend_list(&self) -> &L {
+        self.friend_list
     }
-}
 
-impl<'a> BoardController<'a> {
     #[inline]
-    pub fn friend_color(&self) -> Color {
-        self.friend_color
+    pub fn enemy_list(&self) -> &L {
+        self.enemy_list
+    }
+
+    /// Network accumulator kept up to date by `make_move` and `unmake_move`, see `nnue`.
+    #[inline]
+    pub fn accumulator(&self) -> Option<&Accumulator> {
+        self.accumulator.as_ref()
+    }
+
+    pub fn set_accumulator(&mut self, accumulator: Option<Accumulator>) {
+        self.accumulator = accumulator;
     }
 
     pub fn friend_movies(&self) -> MoveList {
@@ -83,43 +114,78 @@ impl<'a> BoardController<'a> {
         move_list.iter().find(|m| **m == *movement).is_some()
     }
 
+    fn list_mut(&mut self, color: Color) -> Option<&mut L> {
+        if color == self.friend_color {
+            Some(self.friend_list)
+        } else if color == self.enemy_color {
+            Some(self.enemy_list)
+        } else {
+            None
+        }
+    }
+
+    /// Exchanges the figures of `p1` and `p2` in the piece lists, the board is left as it is.
+    fn swap_lists(&mut self, p1: Point, p2: Point) {
+        let (c1, c2) = (self.board.point(p1).color(), self.board.point(p2).color());
+        if c1 == c2 {
+            if let Some(list) = self.list_mut(c1) { list.swap(p1, p2) }
+            return;
+        }
+        if let Some(list) = self.list_mut(c1) { list.make_move(&Move::new(p1, p2, SIMPLE)) }
+        if let Some(list) = self.list_mut(c2) { list.make_move(&Move::new(p2, p1, SIMPLE)) }
+    }
+
     pub fn make_move(&mut self, movement: &Move) -> (PointInfo, PointInfo) {
-        let mut from_info = PointInfo::new(&movement.from, self);
-        let mut to_info = PointInfo::new(&movement.to, self);
+        let from_info = PointInfo::new(&movement.from, self);
+        let to_info = PointInfo::new(&movement.to, self);
 
         match movement.m_type {
             MoveType::SIMPLE => {
-                from_info.cursor.point_set(movement.to);
-                if self.board.point_mut(movement.to).color() == self.enemy_color {
-                    to_info.cursor.remove();
+                if to_info.figure.color() == self.enemy_color {
+                    self.enemy_list.remove(movement.to);
                 }
-                *self.board.point_mut(movement.to) = *self.board.point_mut(movement.from);
-                *self.board.point_mut(movement.from) = Figure::empty();
-
+                self.friend_list.make_move(movement);
+                self.board.set_point(movement.to, from_info.figure);
+                self.board.set_point(movement.from, Figure::empty());
             }
             MoveType::SWAP => {
-                from_info.cursor.point_set(movement.to);
-                to_info.cursor.point_set(movement.from);
+                self.swap_lists(movement.from, movement.to);
                 self.board.swap(movement.from, movement.to);
             }
             MoveType::TRANSFORM => {
-                from_info.cursor.point_set(movement.to);
-                let f = *self.board.point(movement.from);
-                *self.board.point_mut(movement.from) = Figure::empty();
-                *self.board.point_mut(movement.to) = Figure::new(QUEEN, f.color(), false);
+                self.friend_list.make_move(movement);
+                self.board.set_point(movement.from, Figure::empty());
+                self.board.set_point(movement.to, Figure::new(movement.promoted_rank(), from_info.figure.color(), false));
+            }
+        }
+
+        if let Some(accumulator) = self.accumulator.as_mut() {
+            for info in [&from_info, &to_info] {
+                accumulator.replace(info.point, info.figure, *self.board.point(info.point));
             }
         }
 
         (from_info, to_info)
     }
 
-    pub fn unmake_move(&mut self, mut move_info: (PointInfo, PointInfo)) {
-        move_info.0.cursor.restore();
-        move_info.1.cursor.restore();
-        move_info.0.cursor.point_set(move_info.0.point);
-        move_info.1.cursor.point_set(move_info.1.point);
-        *self.board.point_mut(move_info.0.point) = move_info.0.figure;
-        *self.board.point_mut(move_info.1.point) = move_info.1.figure;
+    pub fn unmake_move(&mut self, move_info: (PointInfo, PointInfo)) {
+        let (from_info, to_info) = move_info;
+        if let Some(accumulator) = self.accumulator.as_mut() {
+            for info in [&from_info, &to_info] {
+                accumulator.replace(info.point, *self.board.point(info.point), info.figure);
+            }
+        }
+
+        if self.board.point(from_info.point).rank() != NONE {
+            // Only a swap leaves a figure on the cell it started from.
+            self.swap_lists(from_info.point, to_info.point);
+        } else {
+            let movement = Move::new(from_info.point, to_info.point, SIMPLE);
+            if let Some(list) = self.list_mut(from_info.figure.color()) { list.unmake_move(&movement) }
+            if let Some(list) = self.list_mut(to_info.figure.color()) { list.restore(to_info.point) }
+        }
+        self.board.set_point(from_info.point, from_info.figure);
+        self.board.set_point(to_info.point, to_info.figure);
     }
 
     pub fn pass_move_to_enemy(&mut self) {
@@ -134,6 +200,24 @@ impl<'a> BoardController<'a> {
         }).is_some()
     }
 
+    /// Whether any enemy figure can take the friend king on the next move.
+    pub fn is_in_check(&self) -> bool {
+        self.friend_list.iter()
+            .find(|p| self.board.point(*p).rank() == KING)
+            .is_some_and(|king| self.board.is_square_attacked(king, self.enemy_color))
+    }
+
+    /// Friend movies which don't leave the friend king under attack.
+    pub fn legal_movies(&mut self) -> MoveList {
+        let mut legal = MoveList::default();
+        for movement in self.friend_movies().iter() {
+            let move_info = self.make_move(movement);
+            if !self.is_in_check() { legal.push(*movement) }
+            self.unmake_move(move_info);
+        }
+        legal
+    }
+
     pub fn find_king_eat_move<'b>(&self, move_list: &'b MoveList) -> Option<&'b Move> {
         move_list.iter()
             .filter(|m| m.m_type == SIMPLE)
@@ -144,16 +228,114 @@ impl<'a> BoardController<'a> {
     }
 }
 
-impl BoardDataHolder {
-    pub fn new(board: &ByteBoard) -> Self {
-        BoardDataHolder {
+impl<B: Board> BoardDataHolder<B> {
+    pub fn new(board: &B) -> Self {
+        BoardDataHolder::with_lists(board)
+    }
+}
+
+impl<B: Board, L: PieceList> BoardDataHolder<B, L> {
+    /// Holder keeping the figures of both sides in `L` piece lists, white to move.
+    pub fn with_lists(board: &B) -> Self {
+        let mut holder = BoardDataHolder {
             board: *board,
-            white_list: FigurePointList::new(board, WHITE),
-            black_list: FigurePointList::new(board, BLACK)
-        }
+            white_list: L::default(),
+            black_list: L::default(),
+            turn: WHITE,
+            halfmove_clock: 0,
+            history: Vec::new(),
+            undone: Vec::new(),
+        };
+        holder.white_list.fill(board, WHITE);
+        holder.black_list.fill(board, BLACK);
+        holder
+    }
+
+    /// Same holder with `color` to move.
+    pub fn with_turn(mut self, color: Color) -> Self {
+        self.turn = color;
+        self
+    }
+
+    /// Side to move next by `play`.
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// Plies since the last capture or pawn move.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Number of the current full move, starting from 1 and growing after every black move.
+    pub fn fullmove_number(&self) -> usize {
+        1 + self.history.iter().filter(|e| e.color == BLACK).count()
+    }
+
+    /// Played movies, oldest first. Movies made through `controller` are not recorded.
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    pub fn moves(&self) -> impl Iterator<Item = &Move> + '_ {
+        self.history.iter().map(|e| &e.movement)
+    }
+
+    /// How many times the current position was already reached since the last capture or pawn move.
+    pub fn repetitions(&self) -> usize {
+        let hash = zobrist::hash(&self.board, self.turn);
+        self.history.iter().rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|e| e.hash == hash)
+            .count()
+    }
+
+    /// Makes `movement` for the side to move and records it, forgetting the undone movies.
+    pub fn play(&mut self, movement: &Move) {
+        self.undone.clear();
+        self.push(movement);
+    }
+
+    /// Takes back the last played move, `None` when there is none.
+    pub fn undo(&mut self) -> Option<Move> {
+        let entry = self.history.pop()?;
+        self.controller(entry.color).unmake_move(entry.undo);
+        self.turn = entry.color;
+        self.halfmove_clock = entry.halfmove_clock;
+        self.undone.push(entry.movement);
+        Some(entry.movement)
+    }
+
+    /// Plays again the last move taken back by `undo`, `None` when there is none.
+    pub fn redo(&mut self) -> Option<Move> {
+        let movement = self.undone.pop()?;
+        self.push(&movement);
+        Some(movement)
+    }
+
+    fn push(&mut self, movement: &Move) {
+        let color = self.turn;
+        let hash = zobrist::hash(&self.board, color);
+        let moving = self.board.point(movement.from).rank();
+        let undo = self.controller(color).make_move(movement);
+
+        let captured = if movement.m_type != MoveType::SWAP && undo.1.figure.color() == color.invert() {
+            undo.1.figure.rank()
+        } else {
+            NONE
+        };
+        self.history.push(HistoryEntry {
+            movement: movement.with_capture(captured),
+            color,
+            undo,
+            hash,
+            halfmove_clock: self.halfmove_clock,
+        });
+        self.halfmove_clock = if moving == PAWN || captured != NONE { 0 } else { self.halfmove_clock + 1 };
+        self.turn = color.invert();
     }
 
-    pub fn controller(&mut self, color: Color) -> BoardController {
+    pub fn controller(&mut self, color: Color) -> BoardController<'_, B, L> {
         match color {
             WHITE =>  {
                 BoardController {
@@ -162,6 +344,7 @@ impl BoardDataHolder {
                     enemy_list: &mut self.black_list,
                     friend_color: WHITE,
                     enemy_color: BLACK,
+                    accumulator: None,
                     position_counter: 0
                 }
             }
@@ -172,6 +355,7 @@ impl BoardDataHolder {
                     enemy_list: &mut self.white_list,
                     friend_color: BLACK,
                     enemy_color: WHITE,
+                    accumulator: None,
                     position_counter: 0
                 }
             }
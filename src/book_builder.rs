@@ -0,0 +1,161 @@
+//! Opening book built from the games stored in the database.
+//!
+//! Every game is replayed from the start position and each of its first `max_plies` movies is
+//! counted under the Polyglot key of the position it was played in, together with the result of
+//! the game for the side which played it. The database keeps no results, so they come from the
+//! end of the game: the side which took the king, or mated, won, anything else, stalemates and
+//! unfinished games included, counts as a draw.
+//!
+//! The book is written in the Polyglot format with a weight of two per win and one per draw, so
+//! movies which only ever lost are left out and `OpeningBook` reads it back like any other book.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rusqlite::Error;
+
+use crate::board::ByteBoard;
+use crate::board_controller::BoardDataHolder;
+use crate::database::{DataBaseInstance, MoveRecord};
+use crate::figure::{Color, Rank};
+use crate::movement::Move;
+use crate::polyglot::{encode_move, polyglot_key, BookEntry, OpeningBook};
+
+pub const DEFAULT_BOOK_PATH: &str = "book.bin";
+pub const DEFAULT_MAX_PLIES: usize = 20;
+
+/// Statistics of one move in one position, results seen by the side which played it.
+#[derive(Default, Debug, Eq, PartialEq, Copy, Clone)]
+pub struct MoveStats {
+    pub count: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl MoveStats {
+    /// Polyglot weight: two per win and one per draw.
+    pub fn weight(&self) -> u32 {
+        self.wins * 2 + self.draws
+    }
+}
+
+pub struct BookBuilder {
+    max_plies: usize,
+    games: usize,
+    stats: HashMap<u64, HashMap<u16, MoveStats>>,
+}
+
+impl BookBuilder {
+    pub fn new(max_plies: usize) -> Self {
+        BookBuilder { max_plies, games: 0, stats: HashMap::new() }
+    }
+
+    /// Number of added games.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Number of distinct positions with statistics.
+    pub fn positions(&self) -> usize {
+        self.stats.len()
+    }
+
+    /// Replays `movies` from the start position, white moving first, and counts them. Returns
+    /// the winner, `None` for a draw, and fails on a move the figure on its cell can't make.
+    pub fn add_game(&mut self, movies: &[Move]) -> Result<Option<Color>, fmt::Error> {
+        let mut holder = BoardDataHolder::new(&ByteBoard::default());
+        let mut color = Color::WHITE;
+        let mut played = Vec::new();
+        let mut winner = None;
+
+        for recorded in movies {
+            let mut controller = holder.controller(color);
+            if controller.board().point(recorded.from).color() != color { return Err(fmt::Error) }
+            // Stored movies keep only their cells, the move type comes from the generator.
+            let movement = controller.point_movies(recorded.from).iter()
+                .find(|m| m.to == recorded.to)
+                .copied()
+                .ok_or(fmt::Error)?;
+
+            played.push((polyglot_key(controller.board(), color), encode_move(&movement), color));
+            let target = *controller.board().point(movement.to);
+            controller.make_move(&movement);
+            if target.rank() == Rank::KING {
+                winner = Some(color);
+                break;
+            }
+            color = color.invert();
+        }
+
+        if winner.is_none() && Self::is_mated(&mut holder, color) {
+            winner = Some(color.invert());
+        }
+
+        for (key, raw_move, color) in played.into_iter().take(self.max_plies) {
+            let stats = self.stats.entry(key).or_default().entry(raw_move).or_default();
+            stats.count += 1;
+            match winner {
+                Some(winner) if winner == color => stats.wins += 1,
+                Some(_) => stats.losses += 1,
+                None => stats.draws += 1,
+            }
+        }
+        self.games += 1;
+        Ok(winner)
+    }
+
+    fn is_mated(holder: &mut BoardDataHolder, color: Color) -> bool {
+        let mut controller = holder.controller(color);
+        controller.is_in_check() && controller.legal_movies().len() == 0
+    }
+
+    /// Adds the game of `records`, ordered by their move numbers.
+    pub fn add_records(&mut self, records: &[MoveRecord]) -> Result<Option<Color>, fmt::Error> {
+        let mut records: Vec<&MoveRecord> = records.iter().collect();
+        records.sort_by_key(|r| r.move_number);
+        let movies = records.iter()
+            .map(|r| r.to_move())
+            .collect::<Result<Vec<_>, _>>()?;
+        self.add_game(&movies)
+    }
+
+    /// Adds every game of `db_instance`, skipping the broken ones. Returns the number of added games.
+    pub fn add_database(&mut self, db_instance: &DataBaseInstance) -> Result<usize, Error> {
+        let mut added = 0;
+        for game in db_instance.find_games()? {
+            let records = db_instance.find_moves_by_game_id(game.id)?;
+            if !records.is_empty() && self.add_records(&records).is_ok() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Statistics of the movies played in the position with Polyglot `key`, by Polyglot move.
+    pub fn stats(&self, key: u64) -> Vec<(u16, MoveStats)> {
+        let mut stats: Vec<_> = self.stats.get(&key)
+            .map(|movies| movies.iter().map(|(m, s)| (*m, *s)).collect())
+            .unwrap_or_default();
+        stats.sort_by_key(|(m, s)| (std::cmp::Reverse(s.weight()), *m));
+        stats
+    }
+
+    /// Book of the movies played at least `min_count` times which won or drew. Weights of a
+    /// position are scaled down together when the heaviest one does not fit `u16`.
+    pub fn build(&self, min_count: u32) -> OpeningBook {
+        let mut entries = Vec::new();
+        for (key, movies) in &self.stats {
+            let movies: Vec<_> = movies.iter()
+                .filter(|(_, s)| s.count >= min_count && s.weight() > 0)
+                .collect();
+            let heaviest = movies.iter().map(|(_, s)| s.weight()).max().unwrap_or(0);
+            let divisor = heaviest / u16::MAX as u32 + 1;
+            for (raw_move, stats) in movies {
+                let weight = (stats.weight() / divisor).max(1) as u16;
+                entries.push(BookEntry { key: *key, raw_move: *raw_move, weight, learn: 0 });
+            }
+        }
+        OpeningBook::new(entries)
+    }
+}
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, Error, params, Result};
 
-use crate::movement::Move;
+use crate::movement::{Move, MoveType};
 use crate::point::Point;
 use std::fmt;
 
@@ -23,9 +23,8 @@ impl Game {
 pub struct MoveRecord {
     pub game_id: i32,
     pub move_number: i32,
-    pub p_from: String,
-    pub p_to: String,
-    pub m_type: String,
+    /// The move in its `Move::pack` form.
+    pub packed: u16,
 }
 
 impl MoveRecord {
@@ -33,9 +32,7 @@ impl MoveRecord {
         MoveRecord {
             game_id: game.id,
             move_number: -1,
-            p_from: String::new(),
-            p_to: String::new(),
-            m_type: String::new(),
+            packed: 0,
         }
     }
 
@@ -43,20 +40,25 @@ impl MoveRecord {
         MoveRecord {
             game_id: self.game_id,
             move_number: self.move_number + 1,
-            p_from: movement.from.to_string(),
-            p_to: movement.to.to_string(),
-            m_type: movement.m_type.to_string()
+            packed: movement.pack(),
         }
     }
 
     pub fn to_move(&self) -> Result<Move, fmt::Error> {
-        let mut m = Move::default();
-        m.from = Point::from_string(&self.p_from)?;
-        m.to = Point::from_string(&self.p_to)?;
-        Ok(m)
+        Move::unpack(self.packed).ok_or(fmt::Error)
     }
 }
 
+/// Packed move of a row stored by the string columns of the old `move_record` table.
+fn pack_old_record(p_from: &str, p_to: &str, m_type: &str) -> Result<u16, fmt::Error> {
+    let m_type = match m_type {
+        "SWAP" => MoveType::SWAP,
+        "TRANSFORM" => MoveType::TRANSFORM,
+        _ => MoveType::SIMPLE,
+    };
+    Ok(Move::new(Point::from_string(p_from)?, Point::from_string(p_to)?, m_type).pack())
+}
+
 pub struct DataBaseInstance {
     connection: Connection
 }
@@ -78,14 +80,49 @@ impl DataBaseInstance {
             "CREATE TABLE IF NOT EXISTS move_record (
                 game_id INTEGER,
                 move_number INTEGER,
-                p_from CHAR(4),
-                p_to CHAR(4),
-                type CHAR(10),
+                move INTEGER NOT NULL,
                 FOREIGN KEY (game_id) REFERENCES game
                 ON DELETE CASCADE
             )", params![],
         )?;
-        Ok(())
+        self.migrate_move_records()
+    }
+
+    /// Rewrites a `move_record` table of the old layout, with the from and to cells and the move
+    /// type as strings, into the packed one. Does nothing when the table is already packed.
+    fn migrate_move_records(&self) -> Result<(), Error> {
+        let columns = self.connection.prepare("PRAGMA table_info(move_record)")?
+            .query_map(params![], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?;
+        if !columns.iter().any(|c| c == "p_from") { return Ok(()) }
+
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute(
+            "CREATE TABLE move_record_packed (
+                game_id INTEGER,
+                move_number INTEGER,
+                move INTEGER NOT NULL,
+                FOREIGN KEY (game_id) REFERENCES game
+                ON DELETE CASCADE
+            )", params![],
+        )?;
+
+        let rows = transaction.prepare("SELECT game_id, move_number, p_from, p_to, type FROM move_record")?
+            .query_map(params![], |row| {
+                Ok((row.get::<_, i32>(0)?, row.get::<_, i32>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, String>(4)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        for (game_id, move_number, p_from, p_to, m_type) in rows {
+            let packed = pack_old_record(&p_from, &p_to, &m_type).map_err(|_| Error::InvalidQuery)?;
+            transaction.execute(
+                "INSERT INTO move_record_packed (game_id, move_number, move) VALUES (?1, ?2, ?3)",
+                params![game_id, move_number, packed],
+            )?;
+        }
+
+        transaction.execute("DROP TABLE move_record", params![])?;
+        transaction.execute("ALTER TABLE move_record_packed RENAME TO move_record", params![])?;
+        transaction.commit()
     }
 
     pub fn add_game(&mut self, game: Game) -> Result<Game, Error> {
@@ -115,27 +152,48 @@ impl DataBaseInstance {
         })
     }
 
+    pub fn find_games(&self) -> Result<Vec<Game>, Error> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, start_time FROM game ORDER BY id",
+        )?;
+
+        let mapped_rows = stmt.query_map(params![], |row| {
+            Ok(Game {
+                id: row.get(0)?,
+                start_time: row.get(1)?,
+            })
+        })?;
+
+        mapped_rows.into_iter().collect::<Result<Vec<_>>>()
+    }
+
     pub fn add_move(&self, record: &MoveRecord) -> Result<(), Error> {
         if record.move_number < 0 { return Err(Error::InvalidQuery) }
         self.connection.execute(
-            "INSERT INTO move_record (game_id, move_number, p_from, p_to, type) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![record.game_id, record.move_number, record.p_from, record.p_to, record.m_type.to_string()],
+            "INSERT INTO move_record (game_id, move_number, move) VALUES (?1, ?2, ?3)",
+            params![record.game_id, record.move_number, record.packed],
         )?;
         return Ok(());
     }
 
+    /// Removes the movies of `game` from `move_number` on, the ones taken back.
+    pub fn remove_moves_from(&self, game: &Game, move_number: i32) -> Result<usize, Error> {
+        self.connection.execute(
+            "DELETE FROM move_record WHERE game_id = ?1 AND move_number >= ?2",
+            params![game.id, move_number],
+        )
+    }
+
     pub fn find_moves_by_game_id(&self, game_id: i32) -> Result<Vec<MoveRecord>, Error> {
         let mut stmt = self.connection.prepare(
-            "SELECT game_id, move_number, p_from, p_to, type FROM move_record WHERE game_id = ?",
+            "SELECT game_id, move_number, move FROM move_record WHERE game_id = ?",
         )?;
 
         let mapped_rows = stmt.query_map(params![game_id], |row| {
             Ok(MoveRecord {
                 game_id: row.get(0)?,
                 move_number: row.get(1)?,
-                p_from: row.get(2)?,
-                p_to: row.get(3)?,
-                m_type: row.get(4)?,
+                packed: row.get(2)?,
             })
         })?;
 
@@ -0,0 +1,210 @@
+//! Endgame knowledge.
+//!
+//! Recognises a few material signatures the ordinary terms get wrong and corrects the score of
+//! the side ahead, the "strong" side:
+//!
+//! - against a bare king with mating material the weak king is driven to the edge and the strong
+//!   king towards it, with a bishop and a knight to a corner of the bishop's color, so that the
+//!   search sees progress long before the mate is within its horizon;
+//! - known drawish endings are scaled down: a lone minor piece, two knights against a bare king,
+//!   a minor piece up without pawns, opposite colored bishops and a rook pawn with a bishop of the
+//!   wrong color (or none) when the weak king holds the promotion corner.
+//!
+//! Scales are out of `SCALE_NORMAL`, bonuses are in centipawns, see `PST_SCALE`.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::board::Board;
+use crate::figure::{Color, Rank, W_KING};
+use crate::figure_list::FigurePointList;
+use crate::point::Point;
+use crate::pst::PST_SCALE;
+
+pub const SCALE_NORMAL: i32 = 64;
+pub const SCALE_OPPOSITE_BISHOPS: i32 = 32;
+pub const SCALE_MINOR_UP: i32 = 16;
+pub const SCALE_TWO_KNIGHTS: i32 = 4;
+pub const SCALE_WRONG_ROOK_PAWN: i32 = 2;
+pub const SCALE_DRAW: i32 = 0;
+
+/// Bonus per step of the weak king away from the center, or towards the right corner.
+pub const PUSH_TO_EDGE: i32 = 20;
+/// Bonus per step the strong king is closer to the weak one.
+pub const PUSH_CLOSE: i32 = 10;
+
+/// Figures of one side, the king not counted.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Material {
+    pub queens: i32,
+    pub rooks: i32,
+    pub bishops: i32,
+    pub knights: i32,
+    pub pawns: i32,
+}
+
+impl Material {
+    pub fn new<B: Board>(board: &B, list: &FigurePointList) -> Self {
+        let mut material = Material::default();
+        for p in list.iter() {
+            match board.point(p).rank() {
+                Rank::QUEEN => material.queens += 1,
+                Rank::ROOK => material.rooks += 1,
+                Rank::BISHOP => material.bishops += 1,
+                Rank::KNIGHT => material.knights += 1,
+                Rank::PAWN => material.pawns += 1,
+                _ => {}
+            }
+        }
+        material
+    }
+
+    pub fn minors(&self) -> i32 {
+        self.bishops + self.knights
+    }
+
+    pub fn pieces(&self) -> i32 {
+        self.queens + self.rooks + self.minors()
+    }
+
+    pub fn is_bare_king(&self) -> bool {
+        self.pieces() == 0 && self.pawns == 0
+    }
+
+    /// Piece material in minor piece units: a rook is worth two, a queen four.
+    fn piece_units(&self) -> i32 {
+        self.queens * 4 + self.rooks * 2 + self.minors()
+    }
+}
+
+/// Signature like `KRB` or `KPPP`.
+impl Display for Material {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "K")?;
+        for (count, c) in [(self.queens, 'Q'), (self.rooks, 'R'), (self.bishops, 'B'), (self.knights, 'N'), (self.pawns, 'P')] {
+            for _ in 0..count { write!(f, "{}", c)? }
+        }
+        Ok(())
+    }
+}
+
+fn king<B: Board>(board: &B, list: &FigurePointList) -> Option<Point> {
+    list.iter().find(|p| board.point(*p).rank() == Rank::KING)
+}
+
+fn figures<B: Board>(board: &B, list: &FigurePointList, rank: Rank) -> Vec<Point> {
+    list.iter().filter(|p| board.point(*p).rank() == rank).collect()
+}
+
+fn is_dark(p: Point) -> bool {
+    (p.x() + p.y()) % 2 == 1
+}
+
+fn distance(a: Point, b: Point) -> i32 {
+    (a.x() - b.x()).abs().max((a.y() - b.y()).abs()) as i32
+}
+
+fn center_distance(p: Point) -> i32 {
+    let edge = |v: i8| (3 - v).max(v - 4) as i32;
+    edge(p.x()) + edge(p.y())
+}
+
+/// Scale of the strong side's advantage, `SCALE_NORMAL` when nothing is recognised.
+fn scale<B: Board>(board: &B, strong_list: &FigurePointList, weak_list: &FigurePointList, strong: &Material, weak: &Material) -> i32 {
+    if strong.pawns == 0 {
+        if strong.pieces() == strong.minors() && strong.minors() <= 1 {
+            return SCALE_DRAW;
+        }
+        if strong.pieces() == 2 && strong.knights == 2 && weak.is_bare_king() {
+            return SCALE_TWO_KNIGHTS;
+        }
+        if strong.piece_units() - weak.piece_units() <= 1 {
+            return SCALE_MINOR_UP;
+        }
+    }
+
+    if strong.pieces() == strong.bishops && strong.bishops <= 1 && strong.pawns > 0 {
+        if let Some(scale) = wrong_rook_pawn(board, strong_list, weak_list) {
+            return scale;
+        }
+    }
+
+    if strong.pieces() == 1 && weak.pieces() == 1 && strong.bishops == 1 && weak.bishops == 1 {
+        let strong_bishop = figures(board, strong_list, Rank::BISHOP)[0];
+        let weak_bishop = figures(board, weak_list, Rank::BISHOP)[0];
+        if is_dark(strong_bishop) != is_dark(weak_bishop) {
+            return SCALE_OPPOSITE_BISHOPS;
+        }
+    }
+    SCALE_NORMAL
+}
+
+/// Pawns only on one rook file, no bishop of the promotion cell's color and the weak king next to it.
+fn wrong_rook_pawn<B: Board>(board: &B, strong_list: &FigurePointList, weak_list: &FigurePointList) -> Option<i32> {
+    let color = board.point(king(board, strong_list)?).color();
+    let pawns = figures(board, strong_list, Rank::PAWN);
+    let file = pawns.first()?.x();
+    if file != 0 && file != 7 { return None }
+    if pawns.iter().any(|p| p.x() != file) { return None }
+
+    let promotion = Point::new(file, if color == Color::WHITE { 7 } else { 0 });
+    if figures(board, strong_list, Rank::BISHOP).iter().any(|b| is_dark(*b) == is_dark(promotion)) { return None }
+
+    let weak_king = king(board, weak_list)?;
+    if distance(weak_king, promotion) <= 1 { Some(SCALE_WRONG_ROOK_PAWN) } else { None }
+}
+
+/// Bonus for driving a bare weak king to the edge, or for a bishop and a knight to the corner
+/// of the bishop's color.
+fn mating_bonus<B: Board>(board: &B, strong_list: &FigurePointList, weak_list: &FigurePointList, strong: &Material) -> i32 {
+    let (strong_king, weak_king) = match (king(board, strong_list), king(board, weak_list)) {
+        (Some(s), Some(w)) => (s, w),
+        _ => return 0,
+    };
+
+    let edge = if strong.queens == 0 && strong.rooks == 0 && strong.bishops == 1 && strong.knights == 1 {
+        let bishop = figures(board, strong_list, Rank::BISHOP)[0];
+        let corner = [Point::new(0, 0), Point::new(7, 0), Point::new(0, 7), Point::new(7, 7)].iter()
+            .filter(|c| is_dark(**c) == is_dark(bishop))
+            .map(|c| distance(*c, weak_king))
+            .min()
+            .unwrap();
+        7 - corner
+    } else {
+        center_distance(weak_king)
+    };
+
+    (PUSH_TO_EDGE * edge + PUSH_CLOSE * (7 - distance(strong_king, weak_king))) * PST_SCALE
+}
+
+/// Mating material without pawns, which rather promote than drive the king.
+fn can_mate(material: &Material) -> bool {
+    material.queens > 0 || material.rooks > 0
+        || material.bishops >= 2 || (material.bishops >= 1 && material.knights >= 1)
+}
+
+fn strong_side_score<B: Board>(board: &B, strong_list: &FigurePointList, weak_list: &FigurePointList, score: i32) -> i32 {
+    let strong = Material::new(board, strong_list);
+    let weak = Material::new(board, weak_list);
+
+    let score = score * scale(board, strong_list, weak_list, &strong, &weak) / SCALE_NORMAL;
+    if weak.is_bare_king() && strong.pawns == 0 && can_mate(&strong) {
+        score + mating_bonus(board, strong_list, weak_list, &strong)
+    } else {
+        score
+    }
+}
+
+/// Corrects `score`, given from the friend point of view, with endgame knowledge.
+/// A score of a captured king is returned as is, no scale may hide it.
+pub fn adjust<B: Board>(board: &B, friend_list: &FigurePointList, enemy_list: &FigurePointList, score: i32) -> i32 {
+    if score.abs() >= W_KING / 2 || king(board, friend_list).is_none() || king(board, enemy_list).is_none() {
+        return score;
+    }
+
+    if score >= 0 {
+        strong_side_score(board, friend_list, enemy_list, score)
+    } else {
+        - strong_side_score(board, enemy_list, friend_list, - score)
+    }
+}
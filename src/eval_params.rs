@@ -0,0 +1,257 @@
+//! Tunable parameters of `TaperedEvaluator`.
+//!
+//! `EvalParams` gathers piece values, piece-square tables and the weights of the pawn structure,
+//! king safety and mobility terms, all in centipawns (see `PST_SCALE`). `DEFAULT_PARAMS` is built
+//! from the constants of the term modules. Parameters are saved as a TOML-like text file of
+//! `name = value` lines, arrays written as `[a, b, ...]` and possibly spanning several lines,
+//! `#` starts a comment. A `[section]` line prefixes the names below it with `section.`, so
+//! `[pawns]` followed by `doubled = [-10, -25]` is the same as `pawns.doubled = [-10, -25]`.
+//! A file may list only some of the groups, the rest keep their default values.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::{fmt, fs, io, slice};
+
+use crate::evaluation::TaperedScore;
+use crate::figure::{Rank, W_BISHOP, W_KNIGHT, W_PAWN, W_QUEEN, W_ROOK};
+use crate::{king_safety, mobility, pawn_structure, pst};
+use crate::pst::PST_SCALE;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvalParams {
+    /// Indexed by `Rank`, the king is not valued here, see `W_KING`.
+    pub piece_values: [i32; 8],
+    /// Indexed by `Rank`, laid out as described in `pst`.
+    pub pst_mg: [[i32; 64]; 8],
+    pub pst_eg: [[i32; 64]; 8],
+
+    pub doubled: TaperedScore,
+    pub isolated: TaperedScore,
+    pub backward: TaperedScore,
+    pub chain: TaperedScore,
+    pub passed: [TaperedScore; 8],
+
+    pub shield_near: i32,
+    pub shield_far: i32,
+    pub shield_missing: i32,
+    pub open_file: i32,
+    pub half_open_file: i32,
+    pub attacker_weight: [i32; 8],
+    pub attack_unit_penalty: i32,
+    pub max_attack_penalty: i32,
+
+    pub mobility: [TaperedScore; 8],
+    pub rook_open_file: TaperedScore,
+    pub rook_half_open_file: TaperedScore,
+    pub rook_seventh_rank: TaperedScore,
+    pub connected_rooks: TaperedScore,
+    pub bishop_pair: TaperedScore,
+    pub knight_outpost: TaperedScore,
+}
+
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    piece_values: [0, 0, W_QUEEN / PST_SCALE, W_ROOK / PST_SCALE, W_BISHOP / PST_SCALE, W_KNIGHT / PST_SCALE, W_PAWN / PST_SCALE, 0],
+    pst_mg: pst::MG_TABLES,
+    pst_eg: pst::EG_TABLES,
+
+    doubled: pawn_structure::DOUBLED,
+    isolated: pawn_structure::ISOLATED,
+    backward: pawn_structure::BACKWARD,
+    chain: pawn_structure::CHAIN,
+    passed: pawn_structure::PASSED,
+
+    shield_near: king_safety::SHIELD_NEAR,
+    shield_far: king_safety::SHIELD_FAR,
+    shield_missing: king_safety::SHIELD_MISSING,
+    open_file: king_safety::OPEN_FILE,
+    half_open_file: king_safety::HALF_OPEN_FILE,
+    attacker_weight: king_safety::ATTACKER_WEIGHT,
+    attack_unit_penalty: king_safety::ATTACK_UNIT_PENALTY,
+    max_attack_penalty: king_safety::MAX_ATTACK_PENALTY,
+
+    mobility: mobility::MOBILITY,
+    rook_open_file: mobility::ROOK_OPEN_FILE,
+    rook_half_open_file: mobility::ROOK_HALF_OPEN_FILE,
+    rook_seventh_rank: mobility::ROOK_SEVENTH_RANK,
+    connected_rooks: mobility::CONNECTED_ROOKS,
+    bishop_pair: mobility::BISHOP_PAIR,
+    knight_outpost: mobility::KNIGHT_OUTPOST,
+};
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_PARAMS
+    }
+}
+
+const RANK_NAMES: [&str; 8] = ["none", "king", "queen", "rook", "bishop", "knight", "pawn", "out"];
+
+const QUEEN: usize = Rank::QUEEN as usize;
+const KNIGHT: usize = Rank::KNIGHT as usize;
+const PAWN: usize = Rank::PAWN as usize;
+const KING: usize = Rank::KING as usize;
+
+fn scores(scores: &mut [TaperedScore]) -> Vec<&mut i32> {
+    scores.iter_mut().flat_map(|s| [&mut s.mg, &mut s.eg]).collect()
+}
+
+fn score(score: &mut TaperedScore) -> Vec<&mut i32> {
+    scores(slice::from_mut(score))
+}
+
+impl EvalParams {
+    /// Calls `f` for every named group of values, always in the same order.
+    /// Values which have no effect on the evaluation, like the king's piece value, are left out.
+    pub fn for_each_group<F: FnMut(String, Vec<&mut i32>)>(&mut self, mut f: F) {
+        f("material".to_string(), self.piece_values[QUEEN..=PAWN].iter_mut().collect());
+        for (rank, name) in RANK_NAMES.iter().enumerate().take(PAWN + 1).skip(KING) {
+            f(format!("pst_mg.{}", name), self.pst_mg[rank].iter_mut().collect());
+            f(format!("pst_eg.{}", name), self.pst_eg[rank].iter_mut().collect());
+        }
+
+        f("pawns.doubled".to_string(), score(&mut self.doubled));
+        f("pawns.isolated".to_string(), score(&mut self.isolated));
+        f("pawns.backward".to_string(), score(&mut self.backward));
+        f("pawns.chain".to_string(), score(&mut self.chain));
+        f("pawns.passed".to_string(), scores(&mut self.passed[1..7]));
+
+        f("king_safety.shield_near".to_string(), vec![&mut self.shield_near]);
+        f("king_safety.shield_far".to_string(), vec![&mut self.shield_far]);
+        f("king_safety.shield_missing".to_string(), vec![&mut self.shield_missing]);
+        f("king_safety.open_file".to_string(), vec![&mut self.open_file]);
+        f("king_safety.half_open_file".to_string(), vec![&mut self.half_open_file]);
+        f("king_safety.attacker_weight".to_string(), self.attacker_weight[QUEEN..=KNIGHT].iter_mut().collect());
+        f("king_safety.attack_unit_penalty".to_string(), vec![&mut self.attack_unit_penalty]);
+        f("king_safety.max_attack_penalty".to_string(), vec![&mut self.max_attack_penalty]);
+
+        f("mobility.per_cell".to_string(), scores(&mut self.mobility[QUEEN..=KNIGHT]));
+        f("mobility.rook_open_file".to_string(), score(&mut self.rook_open_file));
+        f("mobility.rook_half_open_file".to_string(), score(&mut self.rook_half_open_file));
+        f("mobility.rook_seventh_rank".to_string(), score(&mut self.rook_seventh_rank));
+        f("mobility.connected_rooks".to_string(), score(&mut self.connected_rooks));
+        f("mobility.bishop_pair".to_string(), score(&mut self.bishop_pair));
+        f("mobility.knight_outpost".to_string(), score(&mut self.knight_outpost));
+    }
+
+    /// All values of `for_each_group` in one flat vector.
+    pub fn values(&self) -> Vec<i32> {
+        let mut values = Vec::new();
+        self.clone().for_each_group(|_, group| values.extend(group.into_iter().map(|v| *v)));
+        values
+    }
+
+    /// Inverse of `values`.
+    pub fn set_values(&mut self, values: &[i32]) {
+        let mut values = values.iter();
+        self.for_each_group(|_, group| group.into_iter().for_each(|v| *v = *values.next().expect("Too few values")));
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Malformed evaluation parameters in {}", path)))
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+fn parse_values(value: &str) -> Result<Vec<i32>, fmt::Error> {
+    let value = value.trim();
+    let list = match value.strip_prefix('[') {
+        None => value,
+        Some(rest) => rest.strip_suffix(']').ok_or(fmt::Error)?,
+    };
+    list.split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<i32>().map_err(|_| fmt::Error))
+        .collect()
+}
+
+impl FromStr for EvalParams {
+    type Err = fmt::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries: HashMap<String, Vec<i32>> = HashMap::new();
+        let mut pending: Option<(String, String)> = None;
+        let mut section = String::new();
+
+        for line in s.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() { continue }
+
+            let (key, value) = match pending.take() {
+                Some((key, value)) => (key, value + " " + line),
+                None => match line.split_once('=') {
+                    None => {
+                        let name = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).ok_or(fmt::Error)?;
+                        section = format!("{}.", name.trim());
+                        continue;
+                    }
+                    Some((key, value)) => (section.clone() + key.trim(), value.trim().to_string()),
+                },
+            };
+
+            if value.starts_with('[') && !value.ends_with(']') {
+                pending = Some((key, value));
+            } else if entries.insert(key, parse_values(&value)?).is_some() {
+                return Err(fmt::Error);
+            }
+        }
+        if pending.is_some() { return Err(fmt::Error) }
+
+        let mut params = EvalParams::default();
+        let mut valid = true;
+        params.for_each_group(|name, group| {
+            if let Some(values) = entries.remove(&name) {
+                valid &= values.len() == group.len();
+                group.into_iter().zip(values).for_each(|(v, value)| *v = value);
+            }
+        });
+
+        if valid && entries.is_empty() { Ok(params) } else { Err(fmt::Error) }
+    }
+}
+
+impl Display for EvalParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut groups = Vec::new();
+        self.clone().for_each_group(|name, group| groups.push((name, group.into_iter().map(|v| *v).collect::<Vec<_>>())));
+
+        let mut section = "";
+        for (name, values) in groups.iter() {
+            let name = match name.split_once('.') {
+                None => name.as_str(),
+                Some((group_section, key)) => {
+                    if group_section != section {
+                        section = group_section;
+                        writeln!(f, "\n[{}]", section)?;
+                    }
+                    key
+                }
+            };
+
+            if values.len() == 1 {
+                writeln!(f, "{} = {}", name, values[0])?;
+                continue;
+            }
+
+            let rows: Vec<String> = values.chunks(8)
+                .map(|row| row.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "))
+                .collect();
+            if rows.len() == 1 {
+                writeln!(f, "{} = [{}]", name, rows[0])?;
+            } else {
+                writeln!(f, "{} = [", name)?;
+                for row in rows.iter() {
+                    writeln!(f, "    {},", row)?;
+                }
+                writeln!(f, "]")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+//! Tapered evaluation.
+//!
+//! Positional terms are kept as a pair of middlegame and endgame scores, `TaperedScore`,
+//! and blended by the game phase: 24 phase units for the full set of knights, bishops,
+//! rooks and queens, falling to 0 as they are exchanged. Material is not tapered.
+//! All weights are taken from the evaluator's `EvalParams`.
+//!
+//! `TaperedEvaluator` sums material, piece-square tables (`pst`), pawn structure (`pawn_structure`),
+//! king safety (`king_safety`) and mobility (`mobility`). `TaperedEvaluator::trace` returns
+//! these terms per side as an `EvalTrace`.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use crate::board::Board;
+use crate::board_controller::BoardController;
+use crate::endgame;
+use crate::eval_params::EvalParams;
+use crate::figure::{Color, Figure, Rank, W_KING};
+use crate::figure_list::FigurePointList;
+use crate::king_safety::king_safety;
+use crate::mobility::mobility;
+use crate::pawn_structure::PawnHashTable;
+use crate::pst::{pst_score, PST_SCALE};
+use crate::score::Evaluator;
+
+pub const MAX_PHASE: i32 = 24;
+
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TaperedScore {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl TaperedScore {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        TaperedScore { mg, eg }
+    }
+
+    /// Blends the halves for `phase` in `0..=MAX_PHASE`, `MAX_PHASE` being the opening.
+    pub fn taper(&self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for TaperedScore {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        TaperedScore::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for TaperedScore {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other
+    }
+}
+
+impl Sub for TaperedScore {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        TaperedScore::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Mul<i32> for TaperedScore {
+    type Output = Self;
+
+    fn mul(self, k: i32) -> Self {
+        TaperedScore::new(self.mg * k, self.eg * k)
+    }
+}
+
+impl Neg for TaperedScore {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        TaperedScore::new(-self.mg, -self.eg)
+    }
+}
+
+fn phase_weight(figure: Figure) -> i32 {
+    match figure.rank() {
+        Rank::KNIGHT | Rank::BISHOP => 1,
+        Rank::ROOK => 2,
+        Rank::QUEEN => 4,
+        _ => 0,
+    }
+}
+
+/// Game phase of the position made of the figures in both lists, see `MAX_PHASE`.
+pub fn game_phase<B: Board>(board: &B, white_list: &FigurePointList, black_list: &FigurePointList) -> i32 {
+    let phase: i32 = white_list.iter().chain(black_list.iter())
+        .map(|p| phase_weight(*board.point(p)))
+        .sum();
+    phase.min(MAX_PHASE)
+}
+
+fn side_pst<B: Board>(params: &EvalParams, board: &B, list: &FigurePointList) -> TaperedScore {
+    list.iter().fold(TaperedScore::default(), |score, p| score + pst_score(params, p, *board.point(p)))
+}
+
+fn side_material<B: Board>(params: &EvalParams, board: &B, list: &FigurePointList) -> i32 {
+    list.iter()
+        .map(|p| match board.point(p).rank() {
+            Rank::KING => W_KING,
+            rank => params.piece_values[rank as usize] * PST_SCALE,
+        })
+        .sum()
+}
+
+/// Evaluation terms of one side. Material is not tapered.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EvalTerms {
+    pub material: i32,
+    pub pst: TaperedScore,
+    pub pawns: TaperedScore,
+    pub king_safety: TaperedScore,
+    pub mobility: TaperedScore,
+}
+
+impl EvalTerms {
+    pub fn positional(&self) -> TaperedScore {
+        self.pst + self.pawns + self.king_safety + self.mobility
+    }
+
+    /// Score of the terms for `phase`, what `TaperedEvaluator` sums up.
+    pub fn score(&self, phase: i32) -> i32 {
+        self.material + self.positional().taper(phase)
+    }
+}
+
+impl Sub for EvalTerms {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        EvalTerms {
+            material: self.material - other.material,
+            pst: self.pst - other.pst,
+            pawns: self.pawns - other.pawns,
+            king_safety: self.king_safety - other.king_safety,
+            mobility: self.mobility - other.mobility,
+        }
+    }
+}
+
+/// Breakdown of `TaperedEvaluator` for both sides, printed as a table by `Display`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EvalTrace {
+    pub phase: i32,
+    pub white: EvalTerms,
+    pub black: EvalTerms,
+    /// Correction of the white point of view score made by `endgame::adjust`.
+    pub endgame: i32,
+}
+
+impl EvalTrace {
+    /// Evaluation from the point of view of `color`, equal to `TaperedEvaluator::evaluate`.
+    pub fn score(&self, color: Color) -> i32 {
+        let white_score = (self.white - self.black).score(self.phase) + self.endgame;
+        if color == Color::BLACK { - white_score } else { white_score }
+    }
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut Formatter<'_>, name: &str, white: TaperedScore, black: TaperedScore| {
+            writeln!(f, "{:<12} {:>7} {:>7} {:>7} {:>7} {:>7}",
+                     name, white.mg, white.eg, black.mg, black.eg, (white - black).taper(self.phase))
+        };
+
+        writeln!(f, "{:<12} {:>7} {:>7} {:>7} {:>7} {:>7}", "Term", "W mg", "W eg", "B mg", "B eg", "Total")?;
+        let (w, b) = (&self.white, &self.black);
+        row(f, "Material", TaperedScore::new(w.material, w.material), TaperedScore::new(b.material, b.material))?;
+        row(f, "PST", w.pst, b.pst)?;
+        row(f, "Pawns", w.pawns, b.pawns)?;
+        row(f, "King safety", w.king_safety, b.king_safety)?;
+        row(f, "Mobility", w.mobility, b.mobility)?;
+        writeln!(f, "{:<12} {:>39}", "Endgame", self.endgame)?;
+        write!(f, "Phase {}/{}, white score {}", self.phase, MAX_PHASE, self.score(Color::WHITE))
+    }
+}
+
+/// Material plus middlegame/endgame piece-square tables, pawn structure, king safety
+/// and mobility, blended by the game phase and corrected by `endgame::adjust`.
+/// Pawn structure is cached in the evaluator's own `PawnHashTable`.
+#[derive(Default)]
+pub struct TaperedEvaluator {
+    params: EvalParams,
+    pawn_table: PawnHashTable,
+}
+
+impl TaperedEvaluator {
+    pub fn new(pawn_table_size_kb: usize) -> Self {
+        TaperedEvaluator { params: EvalParams::default(), pawn_table: PawnHashTable::new(pawn_table_size_kb) }
+    }
+
+    pub fn with_params(params: EvalParams) -> Self {
+        TaperedEvaluator { params, pawn_table: PawnHashTable::default() }
+    }
+
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Replaces the parameters, the pawn table cached with the old ones is cleared.
+    pub fn set_params(&mut self, params: EvalParams) {
+        self.params = params;
+        self.pawn_table.clear();
+    }
+
+    fn terms<B: Board>(&self, board: &B, friend: &FigurePointList, enemy: &FigurePointList, pawns: TaperedScore) -> EvalTerms {
+        EvalTerms {
+            material: side_material(&self.params, board, friend),
+            pst: side_pst(&self.params, board, friend),
+            pawns,
+            king_safety: king_safety(&self.params, board, friend, enemy),
+            mobility: mobility(&self.params, board, friend, enemy),
+        }
+    }
+
+    /// Evaluation terms of both sides of the controller's position.
+    pub fn trace<B: Board>(&self, controller: &BoardController<B>) -> EvalTrace {
+        let board = controller.board();
+        let (friend, enemy) = (controller.friend_list(), controller.enemy_list());
+        let (white_list, black_list) = if controller.friend_color() == Color::WHITE { (friend, enemy) } else { (enemy, friend) };
+
+        let pawns = self.pawn_table.evaluate(&self.params, board);
+        let phase = game_phase(board, white_list, black_list);
+        let white = self.terms(board, white_list, black_list, pawns.white);
+        let black = self.terms(board, black_list, white_list, pawns.black);
+
+        let score = (white - black).score(phase);
+        let endgame = endgame::adjust(board, white_list, black_list, score) - score;
+        EvalTrace { phase, white, black, endgame }
+    }
+}
+
+impl<B: Board> Evaluator<B> for TaperedEvaluator {
+    fn evaluate(&self, controller: &BoardController<B>) -> i32 {
+        let board = controller.board();
+        let (friend, enemy) = (controller.friend_list(), controller.enemy_list());
+        let pawns = self.pawn_table.evaluate(&self.params, board);
+
+        let friend_terms = self.terms(board, friend, enemy, pawns.side(controller.friend_color()));
+        let enemy_terms = self.terms(board, enemy, friend, pawns.side(controller.enemy_color()));
+        let score = (friend_terms - enemy_terms).score(game_phase(board, friend, enemy));
+        endgame::adjust(board, friend, enemy, score)
+    }
+}
@@ -0,0 +1,66 @@
+//! Reading positions from Forsyth–Edwards Notation.
+//!
+//! Only the piece placement and the side to move fields are used, castling rights,
+//! en passant square and clocks are accepted but ignored as `ByteBoard` has no place for them.
+//! FEN lists files from A to H, while `Point::x` counts them from H, so the file index is mirrored.
+//! Positions go through `PositionBuilder`, so illegal setups are refused like malformed text.
+
+use crate::board::ByteBoard;
+use crate::figure::{Color, Figure, Rank};
+use crate::position::{PositionBuilder, PositionError};
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+fn parse_figure(c: char) -> Option<Figure> {
+    let color = if c.is_ascii_uppercase() { Color::WHITE } else { Color::BLACK };
+    let rank = match c.to_ascii_lowercase() {
+        'k' => Rank::KING,
+        'q' => Rank::QUEEN,
+        'r' => Rank::ROOK,
+        'b' => Rank::BISHOP,
+        'n' => Rank::KNIGHT,
+        'p' => Rank::PAWN,
+        _ => return None,
+    };
+    Some(Figure::new(rank, color, false))
+}
+
+/// Parses `fen` into a valid board and the color to move.
+pub fn parse(fen: &str) -> Result<(ByteBoard, Color), PositionError> {
+    let (board, color) = parse_unchecked(fen)?;
+    PositionBuilder::from_board(&board).with_turn(color).build()
+}
+
+/// `parse` which only checks the text, for positions the engine should never see but a
+/// tool or a test has to, like illegal ones.
+pub fn parse_unchecked(fen: &str) -> Result<(ByteBoard, Color), PositionError> {
+    let mut fields = fen.split_whitespace();
+    let placement = fields.next().ok_or(PositionError::FORMAT)?;
+
+    let mut board = ByteBoard::empty();
+    let rows: Vec<&str> = placement.split('/').collect();
+    if rows.len() != 8 { return Err(PositionError::FORMAT) }
+
+    for (i, row) in rows.iter().enumerate() {
+        let number = 7 - i as isize;
+        let mut file = 0isize;
+        for c in row.chars() {
+            if let Some(skip) = c.to_digit(10) {
+                file += skip as isize;
+            } else {
+                if file > 7 { return Err(PositionError::FORMAT) }
+                *board.cell_mut(7 - file, number) = parse_figure(c).ok_or(PositionError::FORMAT)?;
+                file += 1;
+            }
+        }
+        if file != 8 { return Err(PositionError::FORMAT) }
+    }
+
+    let color = match fields.next() {
+        None | Some("w") => Color::WHITE,
+        Some("b") => Color::BLACK,
+        Some(_) => return Err(PositionError::FORMAT),
+    };
+
+    Ok((board, color))
+}
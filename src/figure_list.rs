@@ -1,18 +1,15 @@
 #![allow(dead_code)]
 
-use core::slice::*;
 use std::collections::linked_list::CursorMut;
 use std::collections::LinkedList;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
-use std::iter::FilterMap;
-use std::ptr;
 
+use crate::bitboard::square;
 use crate::board::*;
 use crate::figure::*;
 use crate::movement::Move;
 use crate::point::*;
-use std::ptr::null_mut;
 
 #[derive(Copy, Clone)]
 pub struct PointArrayNode {
@@ -29,379 +26,324 @@ impl PointArrayNode {
     }
 }
 
-#[derive(Copy, Clone)]
-pub struct PointLinkedNode {
-    pub point: Point,
-    pub next: *mut PointLinkedNode,
+/// Cells of the figures of one side as `BoardController` keeps them, heaviest figures first.
+///
+/// Captures are undone in reverse order: `restore` brings back the figure removed last, so a
+/// list returns to its original order once every move made on it is unmade.
+///
+/// `FigurePointList` is the default one, it is the fastest in `benches/figure_list_bench.rs`.
+pub trait PieceList: Default {
+    /// Replaces the content with the cells of the `color` figures of `board`.
+    fn fill<B: Board>(&mut self, board: &B, color: Color);
+
+    fn iter(&self) -> impl Iterator<Item = Point> + '_;
+
+    /// Moves the figure standing on `movement.from` to `movement.to`.
+    fn make_move(&mut self, movement: &Move);
+
+    /// Moves the figure standing on `movement.to` back to `movement.from`.
+    fn unmake_move(&mut self, movement: &Move);
+
+    /// Removes the captured figure standing on `point`.
+    fn remove(&mut self, point: Point);
+
+    /// Brings back the figure removed last, on `point`.
+    fn restore(&mut self, point: Point);
+
+    /// Exchanges the cells of the figures standing on `p1` and `p2`.
+    fn swap(&mut self, p1: Point, p2: Point);
 }
 
-impl PointLinkedNode {
-    pub fn new() -> Self {
-        PointLinkedNode {
-            point: Point::default(),
-            next: ptr::null_mut()
-        }
-    }
+/// Cells of the `color` figures of `board`, heaviest first.
+fn sorted_points<B: Board>(board: &B, color: Color) -> Vec<Point> {
+    let mut points: Vec<_> = board.cell_iter()
+        .filter_map(|(p, f)| if f.color() == color { Some(p) } else { None })
+        .collect();
+    points.sort_by_key(|p| std::cmp::Reverse(board.point(*p).weight()));
+    points
 }
 
-pub struct LinkedNodeCursor {
-    first: *mut *mut PointLinkedNode,
-    prev: *mut PointLinkedNode,
-    pub(crate) cur: *mut PointLinkedNode
+/// `PieceList` over a fixed array, removed figures are flagged and skipped.
+pub struct FigureArrayList {
+    buffer: [PointArrayNode; 16],
+    len: usize,
+    removed: Vec<usize>,
 }
 
-impl Default for LinkedNodeCursor {
-    fn default() -> Self {
-        LinkedNodeCursor {
-            first: null_mut(),
-            prev: null_mut(),
-            cur: null_mut()
-        }
+impl FigureArrayList {
+    pub fn new<B: Board>(board: &B, color: Color) -> Self {
+        let mut list = FigureArrayList::default();
+        list.fill(board, color);
+        list
     }
-}
 
-impl LinkedNodeCursor {
-    pub fn remove(&mut self) {
-        let cur = unsafe { self.cur.as_ref() }.unwrap();
-        match unsafe { self.prev.as_mut() } {
-            None => {
-                let first = unsafe { self.first.as_mut() }.unwrap();
-                *first = cur.next;
-            }
-            Some(prev) => {
-                prev.next = cur.next;
-            }
-        }
+    fn find(&self, point: Point) -> usize {
+        self.buffer[..self.len].iter()
+            .position(|pa| pa.is_present && pa.point == point)
+            .expect("Point should be in list")
     }
+}
 
-    pub fn restore(&mut self) {
-        match unsafe { self.prev.as_mut() } {
-            None => {
-                match unsafe { self.first.as_mut() } {
-                    None => return,
-                    Some(first) => *first = self.cur,
-                }
-            }
-            Some(prev) => {
-                prev.next = self.cur;
-            }
+impl PieceList for FigureArrayList {
+    fn fill<B: Board>(&mut self, board: &B, color: Color) {
+        *self = FigureArrayList::default();
+        for p in sorted_points(board, color) {
+            self.buffer[self.len] = PointArrayNode { point: p, is_present: true };
+            self.len += 1;
         }
     }
 
-    pub fn point_set(&mut self, point: Point) {
-        match unsafe { self.cur.as_mut() } {
-            None => {}
-            Some(cur) => cur.point = point,
-        }
+    fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.buffer[..self.len].iter().filter_map(|pa| if pa.is_present { Some(pa.point) } else { None })
     }
 
-    pub fn point(&self) -> Point {
-        let cur = unsafe { self.cur.as_ref() }.unwrap();
-        cur.point
+    fn make_move(&mut self, movement: &Move) {
+        let i = self.find(movement.from);
+        self.buffer[i].point = movement.to;
     }
-}
 
-#[derive(Copy, Clone)]
-pub struct PointLinkedNodeIterator {
-    cur: *mut PointLinkedNode
-}
+    fn unmake_move(&mut self, movement: &Move) {
+        let i = self.find(movement.to);
+        self.buffer[i].point = movement.from;
+    }
 
-impl Iterator for PointLinkedNodeIterator {
-    type Item = Point;
+    fn remove(&mut self, point: Point) {
+        let i = self.find(point);
+        self.buffer[i].is_present = false;
+        self.removed.push(i);
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match unsafe { self.cur.as_ref() } {
-            None => None,
-            Some(e) => {
-                self.cur = e.next;
-                Some(e.point)
-            }
-        }
+    fn restore(&mut self, point: Point) {
+        let i = self.removed.pop().expect("No removed figure");
+        self.buffer[i] = PointArrayNode { point, is_present: true };
     }
-}
 
-#[derive(Copy, Clone)]
-pub struct LinkedNodeIterator {
-    first: *mut *mut PointLinkedNode,
-    prev: *mut PointLinkedNode,
-    cur: *mut PointLinkedNode
+    fn swap(&mut self, p1: Point, p2: Point) {
+        let (i, j) = (self.find(p1), self.find(p2));
+        self.buffer[i].point = p2;
+        self.buffer[j].point = p1;
+    }
 }
 
-impl Iterator for LinkedNodeIterator {
-    type Item = LinkedNodeCursor;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match unsafe { self.cur.as_mut() } {
-            None => None,
-            Some(cur) => {
-                let cursor = LinkedNodeCursor {
-                    first: self.first,
-                    prev: self.prev,
-                    cur: self.cur
-                };
-                self.prev = self.cur;
-                self.cur = cur.next;
-                Some(cursor)
-            }
-        }
+impl Default for FigureArrayList {
+    fn default() -> Self {
+        FigureArrayList { buffer: [PointArrayNode::new(); 16], len: 0, removed: Vec::with_capacity(16) }
     }
 }
 
-pub struct FigureArrayList {
-    buffer: [PointArrayNode; 16]
+/// `PieceList` over `std::collections::LinkedList`, removed figures are unlinked.
+pub struct FigureLinkedList {
+    list: LinkedList<Point>,
+    removed: Vec<usize>,
 }
 
-impl FigureArrayList {
-    pub fn new(board: &ByteBoard, color: Color) -> Self {
-        let mut list = FigureArrayList::default();
+impl FigureLinkedList {
+    pub fn new<B: Board>(board: &B, color: Color) -> Self {
+        let mut list = FigureLinkedList::default();
         list.fill(board, color);
-        return list;
+        list
+    }
+
+    /// Cursor at the node of `point` with its index.
+    fn find(&mut self, point: Point) -> (usize, CursorMut<'_, Point>) {
+        let mut cursor = self.list.cursor_front_mut();
+        let mut index = 0;
+        while cursor.current().is_some_and(|p| *p != point) {
+            cursor.move_next();
+            index += 1;
+        }
+        (index, cursor)
     }
 
-    pub fn fill(&mut self, board: &ByteBoard, color: Color) {
-        board.cell_iter()
-            .filter_map(|(p, f)| if f.color() == color { Some(p) } else { None })
-            .enumerate()
-            .for_each(|(i, p)| self.buffer[i] = PointArrayNode{ point: p, is_present: true });
+    fn set(&mut self, from: Point, to: Point) {
+        let (_, mut cursor) = self.find(from);
+        *cursor.current().expect("Point should be in list") = to;
+    }
+}
 
-        self.buffer.sort_by(|a, b| {
-            board.point(b.point).weight().cmp(&board.point(a.point).weight())
-        });
+impl PieceList for FigureLinkedList {
+    fn fill<B: Board>(&mut self, board: &B, color: Color) {
+        *self = FigureLinkedList::default();
+        self.list = sorted_points(board, color).into_iter().collect();
     }
 
-    fn find(&self, point: Point, is_present: bool) -> Option<usize> {
-        self.buffer.iter().position(|pa| {
-            pa.is_present == is_present && pa.point == point
-        })
+    fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.list.iter().copied()
     }
 
-    pub fn make_move(&mut self, movement: &Move) {
-        match self.find(movement.from, true) {
-            None => unreachable!(),
-            Some(i) => {
-                self.buffer[i].point = movement.to;
-            }
-        }
+    fn make_move(&mut self, movement: &Move) {
+        self.set(movement.from, movement.to);
     }
 
-    pub fn unmake_move(&mut self, movement: &Move) {
-        match self.find(movement.to, true) {
-            None => unreachable!(),
-            Some(i) => {
-                self.buffer[i].point = movement.from
-            }
-        }
+    fn unmake_move(&mut self, movement: &Move) {
+        self.set(movement.to, movement.from);
     }
 
-    pub fn remove(&mut self, point: Point) {
-        match self.find(point, true) {
-            None => unreachable!(),
-            Some(i) => {
-                self.buffer[i].is_present = false
-            }
-        }
+    fn remove(&mut self, point: Point) {
+        let (index, mut cursor) = self.find(point);
+        cursor.remove_current().expect("Point should be in list");
+        self.removed.push(index);
     }
 
-    pub fn restore(&mut self, point: Point) {
-        match self.find(point, false) {
-            None => unreachable!(),
-            Some(i) => {
-                self.buffer[i].is_present = true
-            }
-        }
+    fn restore(&mut self, point: Point) {
+        let index = self.removed.pop().expect("No removed figure");
+        let mut cursor = self.list.cursor_front_mut();
+        for _ in 0..index { cursor.move_next() }
+        cursor.insert_before(point);
     }
 
-    pub fn iter(&self) -> FilterMap<Iter<'_, PointArrayNode>, fn(&'_ PointArrayNode) -> Option<Point>> {
-        self.buffer.iter().filter_map(|pa| if pa.is_present { Some(pa.point) } else { None } )
+    fn swap(&mut self, p1: Point, p2: Point) {
+        for p in self.list.iter_mut() {
+            if *p == p1 { *p = p2 } else if *p == p2 { *p = p1 }
+        }
     }
 }
 
-impl Default for FigureArrayList {
+impl Default for FigureLinkedList {
     fn default() -> Self {
-        FigureArrayList { buffer: [PointArrayNode::new(); 16] }
+        FigureLinkedList { list: LinkedList::new(), removed: Vec::with_capacity(16) }
     }
 }
 
-pub struct FigureLinkedList {
-    list: LinkedList<Point>
+const NO_SLOT: u8 = u8::MAX;
+
+/// Cells of the figures of one side, ordered by descending weight.
+///
+/// Every figure keeps its slot for the whole game, a removed one only leaves the `present` mask,
+/// so removing and restoring it brings the list back to exactly the same order. A square to slot
+/// table finds the slot of a cell, which makes moving, removing and restoring O(1).
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct FigurePointList {
+    points: [Point; 16],
+    present: u16,
+    slots: [u8; 64],
+    /// Slots of the removed figures, the last removed on top.
+    removed: [u8; 16],
+    removed_len: u8,
 }
 
-impl FigureLinkedList {
-    pub fn new(board: &ByteBoard, color: Color) -> Self {
-        let mut list = FigureLinkedList::default();
+impl FigurePointList {
+    pub fn new<B: Board>(board: &B, color: Color) -> Self {
+        let mut list = FigurePointList::default();
         list.fill(board, color);
-        return list;
+        list
     }
 
-    pub fn fill(&mut self, board: &ByteBoard, color: Color) {
-        let mut vec: Vec<_> = board.cell_iter()
-            .filter_map(|(p, f)| if f.color() == color { Some(p) } else { None })
-            .collect();
+    pub fn len(&self) -> usize {
+        self.present.count_ones() as usize
+    }
 
-        vec.sort_by(|a, b| {
-            board.point(*b).weight().cmp(&board.point(*a).weight())
-        });
+    pub fn is_empty(&self) -> bool {
+        self.present == 0
+    }
 
-        self.list = vec.iter().map(|p| *p).collect();
+    /// Slot of the figure standing on `point`.
+    #[inline]
+    pub fn slot(&self, point: Point) -> Option<usize> {
+        match self.slots[square(point)] {
+            NO_SLOT => None,
+            slot => Some(slot as usize),
+        }
     }
 
-    fn find(&mut self, point: Point) -> CursorMut<Point> {
-        let mut cursor = self.list.cursor_front_mut();
+    pub fn contains(&self, point: Point) -> bool {
+        self.slot(point).is_some()
+    }
 
-        loop {
-            match cursor.current() {
-                None => break cursor,
-                Some(p) => {
-                    if *p == point {
-                        break cursor;
-                    }
-                }
-            }
-            cursor.move_next();
+    /// Puts the figure of `slot` on `point`, restoring it when it was removed.
+    #[inline]
+    pub fn place(&mut self, slot: usize, point: Point) {
+        let old = square(self.points[slot]);
+        if self.present & 1 << slot != 0 && self.slots[old] == slot as u8 {
+            self.slots[old] = NO_SLOT;
         }
+        self.points[slot] = point;
+        self.slots[square(point)] = slot as u8;
+        self.present |= 1 << slot;
     }
 
-    pub fn make_move(&mut self, movement: &Move) {
-        let mut cursor = self.find(movement.from);
-        match cursor.current() {
-            Some(p) => {
-                *p = movement.to;
-            }
-            None => unreachable!()
-        }
+    /// Moves the figure standing on `from` to `to`, returns its slot.
+    pub fn move_point(&mut self, from: Point, to: Point) -> Option<usize> {
+        let slot = self.slot(from)?;
+        self.place(slot, to);
+        Some(slot)
+    }
+
+    pub fn iter(&self) -> FigurePointIter<'_> {
+        FigurePointIter { points: &self.points, present: self.present }
     }
+}
 
-    pub fn unmake_move(&mut self, movement: &Move) {
-        let mut cursor = self.find(movement.to);
-        match cursor.current() {
-            Some(p) => {
-                *p = movement.from;
-            }
-            None => unreachable!()
+impl PieceList for FigurePointList {
+    fn fill<B: Board>(&mut self, board: &B, color: Color) {
+        *self = FigurePointList::default();
+        for (slot, p) in sorted_points(board, color).into_iter().enumerate() {
+            self.place(slot, p);
         }
     }
 
-    pub fn remove(&mut self, point: Point) {
-        let mut cursor = self.find(point);
-        if cursor.current().is_none() { return; }
-        cursor.remove_current();
+    fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        FigurePointList::iter(self)
+    }
+
+    #[inline]
+    fn make_move(&mut self, movement: &Move) {
+        self.move_point(movement.from, movement.to).expect("Point should be in list");
     }
 
-    pub fn restore(&mut self, point: Point) {
-        self.list.push_front(point);
+    #[inline]
+    fn unmake_move(&mut self, movement: &Move) {
+        self.move_point(movement.to, movement.from).expect("Point should be in list");
     }
 
-    pub fn iter(&self) -> std::collections::linked_list::Iter<'_, Point> {
-        self.list.iter()
+    #[inline]
+    fn remove(&mut self, point: Point) {
+        let slot = self.slot(point).expect("Point should be in list");
+        self.slots[square(point)] = NO_SLOT;
+        self.present &= !(1 << slot);
+        self.removed[self.removed_len as usize] = slot as u8;
+        self.removed_len += 1;
+    }
+
+    #[inline]
+    fn restore(&mut self, point: Point) {
+        self.removed_len -= 1;
+        self.place(self.removed[self.removed_len as usize] as usize, point);
+    }
+
+    fn swap(&mut self, p1: Point, p2: Point) {
+        let (s1, s2) = (self.slot(p1).expect("Point should be in list"), self.slot(p2).expect("Point should be in list"));
+        self.place(s1, p2);
+        self.place(s2, p1);
     }
 }
 
-impl Default for FigureLinkedList {
+impl Default for FigurePointList {
     fn default() -> Self {
-        FigureLinkedList { list: LinkedList::new() }
+        FigurePointList { points: [Point::default(); 16], present: 0, slots: [NO_SLOT; 64], removed: [0; 16], removed_len: 0 }
     }
 }
 
-pub struct FigurePointList {
-    pub nodes: Box<[PointLinkedNode; 16]>,
-    pub first: *mut PointLinkedNode,
+#[derive(Copy, Clone)]
+pub struct FigurePointIter<'a> {
+    points: &'a [Point; 16],
+    present: u16,
 }
 
-impl FigurePointList {
-    pub fn new(board: &ByteBoard, color: Color) -> Self {
-        let mut list = FigurePointList::default();
-        list.fill(board, color);
-        return list;
-    }
-
-    pub fn fill(&mut self, board: &ByteBoard, color: Color) {
-        let mut counter = 0;
-        board.cell_iter().for_each(|(p, f)| {
-            if f.color() == color {
-                self.nodes[counter].point = p;
-                counter += 1;
-            }
-        });
-        if counter == 0 {
-            self.first = ptr::null_mut();
-            return;
-        }
+impl<'a> Iterator for FigurePointIter<'a> {
+    type Item = Point;
 
-        self.nodes.sort_by(|a, b| {
-            board.point(b.point).weight().cmp(&board.point(a.point).weight())
-        });
-        for i in 0..(counter - 1) {
-            self.nodes[i].next = &mut self.nodes[i+1]
-        }
-        self.first = &mut self.nodes[0];
-    }
-
-    // pub fn make_move(&mut self, movement: &Move) -> LinkedNodeCursor {
-    //     let node = self.node_iter()
-    //         .find(|n| n.point == movement.from)
-    //         .unwrap();
-    //
-    //     node.point = movement.to;
-    //     return LinkedNodeCursor { first: &mut self.first, prev: null_mut(), cur: node };
-    // }
-    //
-    // pub fn unmake_move(&mut self, movement: &Move, node: LinkedNodeCursor) {
-    //     unsafe { (*node.cur).point = movement.from; }
-    // }
-    //
-    // pub fn remove(&mut self, point: Point) -> LinkedNodeCursor {
-    //     let mut restore_info = LinkedNodeCursor::default();
-    //
-    //     for node in self.node_iter() {
-    //         restore_info.prev = restore_info.cur;
-    //         restore_info.cur = node;
-    //         if node.point == point {
-    //             break;
-    //         }
-    //     }
-    //
-    //     if restore_info.cur.is_null() { return restore_info; }
-    //
-    //     if restore_info.prev.is_null() {
-    //         self.first = unsafe { (*restore_info.cur).next }
-    //     } else {
-    //         unsafe {
-    //             (*restore_info.prev).next = (*restore_info.cur).next;
-    //         }
-    //     }
-    //     return restore_info;
-    // }
-    //
-    // pub fn restore(&mut self, restore_info: LinkedNodeCursor) {
-    //     let cur = match unsafe { restore_info.cur.as_mut() } {
-    //         None => return,
-    //         Some(cur) => cur
-    //     };
-    //
-    //     match unsafe { restore_info.prev.as_mut() } {
-    //         None => {
-    //             cur.next = self.first;
-    //             self.first = cur;
-    //         }
-    //         Some(prev) => {
-    //             cur.next = prev.next;
-    //             prev.next = cur;
-    //         }
-    //     }
-    // }
-
-    pub fn iter(&self) -> PointLinkedNodeIterator {
-        PointLinkedNodeIterator { cur : self.first }
-    }
-
-    pub fn node_iter(&mut self) -> LinkedNodeIterator {
-        LinkedNodeIterator { first: &mut self.first, prev: null_mut(), cur : self.first }
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.present == 0 { return None }
+        let slot = self.present.trailing_zeros() as usize;
+        self.present &= self.present - 1;
+        Some(self.points[slot])
     }
-}
 
-impl Default for FigurePointList {
-    fn default() -> Self {
-        FigurePointList { nodes: Box::new([PointLinkedNode::new(); 16]), first: ptr::null_mut() }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.present.count_ones() as usize;
+        (len, Some(len))
     }
 }
 
@@ -0,0 +1,87 @@
+//! King safety evaluation.
+//!
+//! Three middlegame terms around a side's king: the pawn shield on the king file and its
+//! neighbours, open and half-open files next to the king, and the weighted number of enemy
+//! figures attacking the king zone (the king cell and its neighbours). All of them have a zero
+//! endgame half, so `TaperedScore::taper` fades them out as material leaves the board.
+//! The constants are the defaults of `EvalParams`, values are in centipawns, see `PST_SCALE`.
+
+use crate::board::Board;
+use crate::eval_params::EvalParams;
+use crate::evaluation::TaperedScore;
+use crate::figure::{Color, Rank};
+use crate::figure_list::FigurePointList;
+use crate::movement::for_each_attack;
+use crate::point::Point;
+use crate::pst::PST_SCALE;
+
+/// Friend pawn one and two ranks in front of the king on the king file or a neighbour file.
+pub const SHIELD_NEAR: i32 = 15;
+pub const SHIELD_FAR: i32 = 8;
+pub const SHIELD_MISSING: i32 = -15;
+/// File next to the king without pawns at all, and without friend pawns only.
+pub const OPEN_FILE: i32 = -25;
+pub const HALF_OPEN_FILE: i32 = -12;
+/// Attack units of an enemy figure reaching the king zone, indexed by `Rank`.
+pub const ATTACKER_WEIGHT: [i32; 8] = [0, 0, 5, 3, 2, 2, 0, 0];
+/// The penalty grows with the square of the attack units, up to `MAX_ATTACK_PENALTY`.
+pub const ATTACK_UNIT_PENALTY: i32 = -3;
+pub const MAX_ATTACK_PENALTY: i32 = -500;
+
+pub(crate) fn has_pawn<B: Board>(board: &B, x: i8, y: i8, color: Color) -> bool {
+    let f = board.point(Point::new(x, y));
+    f.rank() == Rank::PAWN && f.color() == color
+}
+
+pub(crate) fn file_has_pawn<B: Board>(board: &B, x: i8, color: Color) -> bool {
+    (0..8).any(|y| has_pawn(board, x, y, color))
+}
+
+fn king_point<B: Board>(board: &B, list: &FigurePointList) -> Option<Point> {
+    list.iter().find(|p| board.point(*p).rank() == Rank::KING)
+}
+
+/// King safety of the side whose figures are in `friend_list`.
+pub fn king_safety<B: Board>(params: &EvalParams, board: &B, friend_list: &FigurePointList, enemy_list: &FigurePointList) -> TaperedScore {
+    let king = match king_point(board, friend_list) {
+        None => return TaperedScore::default(),
+        Some(king) => king,
+    };
+    let color = board.point(king).color();
+    let enemy_color = color.invert();
+    let forward: i8 = if color == Color::WHITE { 1 } else { -1 };
+    let files = (king.x() - 1).max(0)..=(king.x() + 1).min(7);
+
+    let mut score = 0;
+    let relative_rank = if color == Color::WHITE { king.y() } else { 7 - king.y() };
+    for x in files.clone() {
+        if relative_rank <= 1 {
+            let near = king.y() + forward;
+            let far = near + forward;
+            score += if has_pawn(board, x, near, color) {
+                params.shield_near
+            } else if has_pawn(board, x, far, color) {
+                params.shield_far
+            } else {
+                params.shield_missing
+            };
+        }
+
+        if !file_has_pawn(board, x, color) {
+            score += if file_has_pawn(board, x, enemy_color) { params.half_open_file } else { params.open_file };
+        }
+    }
+
+    let in_zone = |p: Point| (p.x() - king.x()).abs() <= 1 && (p.y() - king.y()).abs() <= 1;
+    let attack_units: i32 = enemy_list.iter()
+        .filter(|p| {
+            let mut attacks_zone = false;
+            for_each_attack(board, *p, |to_p| attacks_zone |= in_zone(to_p));
+            attacks_zone
+        })
+        .map(|p| params.attacker_weight[board.point(p).rank() as usize])
+        .sum();
+    score += (params.attack_unit_penalty * attack_units * attack_units).max(params.max_attack_penalty);
+
+    TaperedScore::new(score * PST_SCALE, 0)
+}
@@ -0,0 +1,111 @@
+//! Lazy SMP: parallel alpha-beta search where threads only communicate
+//! through a shared lock-free `TranspositionTable`.
+//!
+//! A search makes and unmakes movies on its board, piece lists and history, so
+//! every helper thread rebuilds its own `BoardDataHolder` from a copy of the
+//! `ByteBoard`, while the calling thread keeps searching on the given controller.
+//! Helpers run iterative deepening as well, odd helpers one ply ahead, and fill the
+//! table with results the main thread picks up. Once the main thread finishes its
+//! last iteration the stop flag is raised and helpers are joined.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::board_controller::{BoardController, BoardDataHolder};
+use crate::evaluation::TaperedEvaluator;
+use crate::figure::W_INFINITY;
+use crate::movement::Move;
+use crate::score::{alpha_betta_with, iterative_deepening, Evaluator, MoveSearch, SearchContext, DEFAULT_ASPIRATION_WINDOW, DEFAULT_MAX_EXTENSION};
+use crate::tablebase::Tablebase;
+use crate::transposition::{TranspositionTable, DEFAULT_TT_SIZE_MB};
+
+pub struct LazySmpSearch {
+    pub threads: usize,
+    pub tt_size_mb: usize,
+    /// Aspiration window of the main thread, see `AlphaBetaSearch::aspiration_window`.
+    pub aspiration_window: i32,
+    /// Extension budget of every thread, see `AlphaBetaSearch::max_extension`.
+    pub max_extension: i32,
+    pub evaluator: Box<dyn Evaluator>,
+    pub tablebase: Option<Arc<Tablebase>>,
+}
+
+impl LazySmpSearch {
+    pub fn new(threads: usize) -> Self {
+        LazySmpSearch::with_evaluator(threads, TaperedEvaluator::default())
+    }
+
+    pub fn with_evaluator<E: Evaluator + 'static>(threads: usize, evaluator: E) -> Self {
+        LazySmpSearch {
+            threads: threads.max(1),
+            tt_size_mb: DEFAULT_TT_SIZE_MB,
+            aspiration_window: DEFAULT_ASPIRATION_WINDOW,
+            max_extension: DEFAULT_MAX_EXTENSION,
+            evaluator: Box::new(evaluator),
+            tablebase: None,
+        }
+    }
+
+    pub fn with_aspiration_window(mut self, aspiration_window: i32) -> Self {
+        self.aspiration_window = aspiration_window;
+        self
+    }
+
+    pub fn with_max_extension(mut self, max_extension: i32) -> Self {
+        self.max_extension = max_extension;
+        self
+    }
+}
+
+impl Default for LazySmpSearch {
+    fn default() -> Self {
+        LazySmpSearch::new(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+}
+
+impl MoveSearch for LazySmpSearch {
+    fn find_best_move(&self, controller: &mut BoardController, depth: i32) -> (i32, Option<Move>) {
+        if let Some((score, movement)) = self.tablebase.as_ref().and_then(|tb| tb.best_move(controller)) {
+            return (score, Some(movement));
+        }
+
+        let tt = TranspositionTable::new(self.tt_size_mb);
+        let stop = AtomicBool::new(false);
+        let context = SearchContext {
+            tt: Some(&tt),
+            stop: Some(&stop),
+            max_extension: self.max_extension,
+            evaluator: self.evaluator.as_ref(),
+            tablebase: self.tablebase.as_deref(),
+        };
+
+        let board = *controller.board;
+        let color = controller.friend_color();
+
+        thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|id| scope.spawn(move || {
+                    let mut holder = BoardDataHolder::new(&board);
+                    let mut helper = holder.controller(color);
+                    context.evaluator.attach(&mut helper);
+                    let mut helper_depth = 1 + (id % 2) as i32;
+                    while helper_depth <= depth && !context.is_stopped() {
+                        alpha_betta_with(&context, &mut helper, helper_depth, - W_INFINITY, W_INFINITY);
+                        helper_depth += 1;
+                    }
+                    helper.position_counter
+                }))
+                .collect();
+
+            self.evaluator.attach(controller);
+            let result = iterative_deepening(&context, controller, depth, self.aspiration_window);
+            stop.store(true, Ordering::Relaxed);
+
+            for helper in helpers {
+                controller.position_counter += helper.join().expect("Search thread panicked");
+            }
+            result
+        })
+    }
+}
@@ -5,6 +5,7 @@
 extern crate enum_display_derive;
 
 pub mod board;
+pub mod bitboard;
 pub mod figure;
 pub mod figure_list;
 pub mod point;
@@ -12,3 +13,22 @@ pub mod movement;
 pub mod board_controller;
 pub mod score;
 pub mod database;
+pub mod zobrist;
+pub mod transposition;
+pub mod lazy_smp;
+pub mod mcts;
+pub mod fen;
+pub mod position;
+pub mod evaluation;
+pub mod eval_params;
+pub mod pst;
+pub mod pawn_structure;
+pub mod king_safety;
+pub mod mobility;
+pub mod endgame;
+pub mod tuning;
+pub mod nnue;
+pub mod tablebase;
+pub mod polyglot;
+pub mod book_builder;
+pub mod perft;
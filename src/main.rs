@@ -1,23 +1,48 @@
 use std::io;
 use std::io::Write;
+use std::sync::Arc;
 
 use chess_algorithm::board::ByteBoard;
 use chess_algorithm::board_controller::{BoardController, BoardDataHolder};
 use chess_algorithm::database::{DataBaseInstance, Game, MoveRecord};
+use chess_algorithm::eval_params::EvalParams;
+use chess_algorithm::evaluation::TaperedEvaluator;
 use chess_algorithm::figure::Color::{BLACK, WHITE};
 use chess_algorithm::figure::Color;
+use chess_algorithm::lazy_smp::LazySmpSearch;
+use chess_algorithm::mcts::MctsSearch;
 use chess_algorithm::movement::Move;
-use chess_algorithm::score::{AlphaBetaSearch, MinMaxSimpleSearch, MoveSearch};
-use std::time::Instant;
+use chess_algorithm::nnue::NnueEvaluator;
+use chess_algorithm::polyglot::OpeningBook;
+use chess_algorithm::score::{AlphaBetaSearch, Evaluator, MinMaxSimpleSearch, MoveSearch};
+use chess_algorithm::tablebase::{Tablebase, DEFAULT_TABLEBASE_DIR};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// What a side does on its turn.
+enum Command {
+    MOVE(Move),
+    /// Take back movies until the side is to move again.
+    UNDO,
+    /// Play again the movies taken back by the last `UNDO`.
+    REDO,
+}
 
 trait MoveSource {
     fn position_counter(&self) -> i32;
-    fn next(&mut self, controller: &mut BoardController) -> Option<Move>;
+    fn next(&mut self, controller: &mut BoardController) -> Option<Command>;
 }
 
 #[derive(Default)]
 struct ConsoleMoveSource {
-    user_input: String
+    user_input: String,
+    /// Traced by the eval command, built with the params picked for this side.
+    evaluator: TaperedEvaluator
+}
+
+impl ConsoleMoveSource {
+    fn new(evaluator: TaperedEvaluator) -> Self {
+        ConsoleMoveSource { evaluator, ..ConsoleMoveSource::default() }
+    }
 }
 
 impl MoveSource for ConsoleMoveSource {
@@ -25,12 +50,12 @@ impl MoveSource for ConsoleMoveSource {
         0
     }
 
-    fn next(&mut self, controller: &mut BoardController<'_>) -> Option<Move> {
+    fn next(&mut self, controller: &mut BoardController<'_>) -> Option<Command> {
         if controller.friend_movies().len() == 0 { return None }
 
         println!();
         loop {
-            print!("Write {:?} move (e.g. a1b2):", controller.friend_color());
+            print!("Write {:?} move (e.g. a1b2), eval, undo or redo:", controller.friend_color());
             io::stdout().flush().unwrap();
 
             self.user_input.clear();
@@ -38,13 +63,20 @@ impl MoveSource for ConsoleMoveSource {
                 .read_line(&mut self.user_input)
                 .expect("Failed to read line");
 
+            if self.user_input.trim().eq_ignore_ascii_case("eval") {
+                println!("{}", self.evaluator.trace(controller));
+                continue;
+            }
+            if self.user_input.trim().eq_ignore_ascii_case("undo") { break Some(Command::UNDO) }
+            if self.user_input.trim().eq_ignore_ascii_case("redo") { break Some(Command::REDO) }
+
             let m = match Move::from_string(&self.user_input.trim_end().to_uppercase()) {
                 Err(_) => continue,
                 Ok(m) => m
             };
 
             if controller.is_valid_move(&m) {
-                break Some(m);
+                break Some(Command::MOVE(m));
             }
         }
     }
@@ -52,16 +84,34 @@ impl MoveSource for ConsoleMoveSource {
 
 struct AlgoMoveSource {
     position_counter: i32,
-    move_search: Box<dyn MoveSearch>
+    move_search: Box<dyn MoveSearch>,
+    book: Option<OpeningBook>,
+    random: u64
 }
 
 impl AlgoMoveSource {
     fn new<T: MoveSearch + 'static>(move_search: T) -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
         Self {
             position_counter: 0,
-            move_search : Box::new(move_search)
+            move_search : Box::new(move_search),
+            book: None,
+            random: seed | 1
         }
     }
+
+    /// Plays from `book` while the position is in it, picking its movies at random by weight.
+    fn with_book(mut self, book: Option<OpeningBook>) -> Self {
+        self.book = book;
+        self
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random
+    }
 }
 
 impl MoveSource for AlgoMoveSource {
@@ -69,14 +119,96 @@ impl MoveSource for AlgoMoveSource {
         self.position_counter
     }
     
-    fn next(&mut self, controller: &mut BoardController<'_>) -> Option<Move> {
+    fn next(&mut self, controller: &mut BoardController<'_>) -> Option<Command> {
+        let random = self.next_random();
+        if let Some(movement) = self.book.as_ref().and_then(|book| book.choose(controller, random)) {
+            self.position_counter = 0;
+            return Some(Command::MOVE(movement));
+        }
+
         let movement = self.move_search.find_best_move(controller, 5).1;
         self.position_counter = controller.position_counter;
-        movement
+        movement.map(Command::MOVE)
+    }
+}
+
+fn read_evaluator(color: Color) -> Box<dyn Evaluator> {
+    loop {
+        print!("Type evaluation params or .nnue network file for {:?} side (empty for default): ", color);
+        io::stdout().flush().unwrap();
+
+        let mut user_input = String::new();
+
+        io::stdin()
+            .read_line(&mut user_input)
+            .expect("Failed to read line");
+
+        let path = user_input.trim();
+        if path.is_empty() {
+            break Box::new(TaperedEvaluator::default());
+        }
+
+        if path.ends_with(".nnue") {
+            match NnueEvaluator::load(path) {
+                Ok(evaluator) => break Box::new(evaluator),
+                Err(e) => println!("{}", e)
+            }
+        } else {
+            match EvalParams::load(path) {
+                Ok(params) => break Box::new(TaperedEvaluator::with_params(params)),
+                Err(e) => println!("{}", e)
+            }
+        }
+    }
+}
+
+fn read_params(color: Color) -> EvalParams {
+    loop {
+        print!("Type evaluation params file for {:?} side eval command (empty for default): ", color);
+        io::stdout().flush().unwrap();
+
+        let mut user_input = String::new();
+
+        io::stdin()
+            .read_line(&mut user_input)
+            .expect("Failed to read line");
+
+        let path = user_input.trim();
+        if path.is_empty() {
+            break EvalParams::default();
+        }
+
+        match EvalParams::load(path) {
+            Ok(params) => break params,
+            Err(e) => println!("{}", e)
+        }
+    }
+}
+
+fn read_book(color: Color) -> Option<OpeningBook> {
+    loop {
+        print!("Type Polyglot opening book file for {:?} side (empty for none): ", color);
+        io::stdout().flush().unwrap();
+
+        let mut user_input = String::new();
+
+        io::stdin()
+            .read_line(&mut user_input)
+            .expect("Failed to read line");
+
+        let path = user_input.trim();
+        if path.is_empty() {
+            break None;
+        }
+
+        match OpeningBook::load(path) {
+            Ok(book) => break Some(book),
+            Err(e) => println!("{}", e)
+        }
     }
 }
 
-fn read_move_source(color: Color) -> Box<dyn MoveSource> {
+fn read_move_source(color: Color, tablebase: &Option<Arc<Tablebase>>) -> Box<dyn MoveSource> {
     loop {
         print!("Type source for {:?} side: ", color);
         io::stdout().flush().unwrap();
@@ -90,9 +222,11 @@ fn read_move_source(color: Color) -> Box<dyn MoveSource> {
         match user_input.trim().parse::<i32>() {
             Ok(n) => {
                 match n {
-                    1 => break Box::new(ConsoleMoveSource::default()),
-                    2 => break Box::new(AlgoMoveSource::new(MinMaxSimpleSearch::default())),
-                    3 => break Box::new(AlgoMoveSource::new(AlphaBetaSearch::default())),
+                    1 => break Box::new(ConsoleMoveSource::new(TaperedEvaluator::with_params(read_params(color)))),
+                    2 => break Box::new(AlgoMoveSource::new(MinMaxSimpleSearch { evaluator: read_evaluator(color) }).with_book(read_book(color))),
+                    3 => break Box::new(AlgoMoveSource::new(AlphaBetaSearch { evaluator: read_evaluator(color), tablebase: tablebase.clone(), ..AlphaBetaSearch::default() }).with_book(read_book(color))),
+                    4 => break Box::new(AlgoMoveSource::new(LazySmpSearch { evaluator: read_evaluator(color), tablebase: tablebase.clone(), ..LazySmpSearch::default() }).with_book(read_book(color))),
+                    5 => break Box::new(AlgoMoveSource::new(MctsSearch { evaluator: read_evaluator(color), ..MctsSearch::default() }).with_book(read_book(color))),
                     _ => {}
                 }
             }
@@ -103,7 +237,7 @@ fn read_move_source(color: Color) -> Box<dyn MoveSource> {
     }
 }
 
-fn load_board(db_instance: &mut DataBaseInstance) -> (ByteBoard, Game, MoveRecord) {
+fn load_game(db_instance: &mut DataBaseInstance) -> (BoardDataHolder, Game) {
     loop {
         print!("Load game or start new: ");
         io::stdout().flush().unwrap();
@@ -116,12 +250,7 @@ fn load_board(db_instance: &mut DataBaseInstance) -> (ByteBoard, Game, MoveRecor
 
         if user_input.trim().is_empty() {
             let game = db_instance.add_game(Game::now()).unwrap();
-            let record = MoveRecord::new(&game);
-            return (
-                ByteBoard::default(),
-                game,
-                record
-            );
+            return (BoardDataHolder::new(&ByteBoard::default()), game);
         }
 
         let id = match user_input.trim().parse::<i32>() {
@@ -167,31 +296,28 @@ fn load_board(db_instance: &mut DataBaseInstance) -> (ByteBoard, Game, MoveRecor
 
         let mut holder = BoardDataHolder::new(&ByteBoard::default());
 
-        move_records.iter().enumerate().for_each(|r| {
-            let c =
-                if r.0 % 2 == 0 {
-                    WHITE
-                } else {
-                    BLACK
-                };
-
-            let mut controller = holder.controller(c);
-            if !controller.is_valid_move(r.1) { unreachable!("{:?} move: {}", c, r.1) };
-            controller.make_move(r.1);
+        for movement in move_records.iter() {
+            let c = holder.turn();
+            if !holder.controller(c).is_valid_move(movement) { unreachable!("{:?} move: {}", c, movement) };
+            holder.play(movement);
 
             println!();
             println!("{}", &holder.board);
-            println!("{:?} move: {}", c, r.1);
-        });
+            println!("{:?} move: {}", c, movement);
+        }
 
-        let mut move_records_result = move_records_result;
-        let last_record = if move_records_result.is_empty() {
-            MoveRecord::new(&game)
-        } else {
-            move_records_result.remove(move_records_result.len() - 1)
-        };
+        return (holder, game);
+    }
+}
 
-        return (holder.board, game, last_record);
+/// Stores the last move of `holder` under its number in the game.
+fn save_last_move(db_instance: &DataBaseInstance, game: &Game, holder: &BoardDataHolder) {
+    if let Some(movement) = holder.moves().last() {
+        let move_record = MoveRecord {
+            move_number: holder.moves().count() as i32 - 1,
+            ..MoveRecord::new(game).to_next(movement)
+        };
+        db_instance.add_move(&move_record).unwrap();
     }
 }
 
@@ -204,16 +330,23 @@ fn main() {
     println!("1: Console gamer");
     println!("2: Simple min-max algorithm");
     println!("3: Alpha-betta algorithm");
+    println!("4: Parallel alpha-betta algorithm (Lazy SMP)");
+    println!("5: Monte Carlo tree search");
     println!();
 
-    let mut white_source: Box<dyn MoveSource> = read_move_source(WHITE);
-    let mut black_source: Box<dyn MoveSource> = read_move_source(BLACK);
+    let tablebase = Tablebase::load_dir(DEFAULT_TABLEBASE_DIR).ok()
+        .filter(|tb| !tb.is_empty())
+        .map(Arc::new);
+    if let Some(tablebase) = &tablebase {
+        println!("Endgame tables: {}", tablebase.signatures().join(", "));
+        println!();
+    }
 
-    let mut db_instance = DataBaseInstance::default();
-    let (board, _, mut move_record) = load_board(&mut db_instance);
+    let mut white_source: Box<dyn MoveSource> = read_move_source(WHITE, &tablebase);
+    let mut black_source: Box<dyn MoveSource> = read_move_source(BLACK, &tablebase);
 
-    let mut board_data_holder = BoardDataHolder::new(&board);
-    let _ = board;
+    let mut db_instance = DataBaseInstance::default();
+    let (mut board_data_holder, game) = load_game(&mut db_instance);
 
     println!();
     println!("===================================");
@@ -222,73 +355,53 @@ fn main() {
     println!();
     println!("{}", &board_data_holder.board);
 
-    if move_record.move_number % 2 == 0 {
-        let timer = Instant::now();
-        let black_move = black_source.next(&mut board_data_holder.controller(BLACK));
-        if black_move.is_none() {
-            println!("Black movements unavailable. Likely it's draw...");
-            return;
-        }
-        board_data_holder.controller(BLACK).make_move(&black_move.unwrap());
-        println!();
-        println!("{}", &board_data_holder.board);
-        println!("black move: {}, {} sec, {} mln positions", black_move.unwrap(), timer.elapsed().as_secs_f32(), black_source.position_counter() as f32 / 1000_000f32);
-
-        move_record = move_record.to_next(&black_move.unwrap());
-        db_instance.add_move(&move_record).unwrap();
-
-        if !board_data_holder.controller(WHITE).is_king_alive() {
-            println!();
-            println!("===================================");
-            println!("=       Black side is win!        =");
-            println!("===================================");
-            return;
-        }
-    }
-
     loop {
-        let timer = Instant::now();
-        let white_move = white_source.next(&mut board_data_holder.controller(WHITE));
-        if white_move.is_none() {
-            println!("White movements unavailable. Likely it's draw...");
-            return;
-        }
-        board_data_holder.controller(WHITE).make_move(&white_move.unwrap());
-        println!();
-        println!("{}", &board_data_holder.board);
-        println!("white move: {}, {} sec, {} mln positions", white_move.unwrap(), timer.elapsed().as_secs_f32(), white_source.position_counter() as f32 / 1000_000f32);
-
-        move_record = move_record.to_next(&white_move.unwrap());
-        db_instance.add_move(&move_record).unwrap();
-
-        if !board_data_holder.controller(BLACK).is_king_alive() {
-            println!();
-            println!("===================================");
-            println!("=      White side is win!         =");
-            println!("===================================");
-            break;
-        }
+        let color = board_data_holder.turn();
+        let source = if color == WHITE { &mut white_source } else { &mut black_source };
 
         let timer = Instant::now();
-        let black_move = black_source.next(&mut board_data_holder.controller(BLACK));
-        if black_move.is_none() {
-            println!("Black movements unavailable. Likely it's draw...");
-            return;
-        }
-        board_data_holder.controller(BLACK).make_move(&black_move.unwrap());
-        println!();
-        println!("{}", &board_data_holder.board);
-        println!("black move: {}, {} sec, {} mln positions", black_move.unwrap(), timer.elapsed().as_secs_f32(), black_source.position_counter() as f32 / 1000_000f32);
-
-        move_record = move_record.to_next(&black_move.unwrap());
-        db_instance.add_move(&move_record).unwrap();
+        let command = match source.next(&mut board_data_holder.controller(color)) {
+            Some(command) => command,
+            None => {
+                println!("{:?} movements unavailable. Likely it's draw...", color);
+                return;
+            }
+        };
 
-        if !board_data_holder.controller(WHITE).is_king_alive() {
-            println!();
-            println!("===================================");
-            println!("=       Black side is win!        =");
-            println!("===================================");
-            break;
+        match command {
+            Command::MOVE(movement) => {
+                board_data_holder.play(&movement);
+                println!();
+                println!("{}", &board_data_holder.board);
+                println!("{:?} move: {}, {} sec, {} mln positions", color, movement, timer.elapsed().as_secs_f32(), source.position_counter() as f32 / 1000_000f32);
+
+                save_last_move(&db_instance, &game, &board_data_holder);
+
+                if !board_data_holder.controller(color.invert()).is_king_alive() {
+                    println!();
+                    println!("===================================");
+                    match color {
+                        WHITE => println!("=      White side is win!         ="),
+                        _ => println!("=       Black side is win!        ="),
+                    }
+                    println!("===================================");
+                    return;
+                }
+            }
+            Command::UNDO => {
+                while board_data_holder.undo().is_some() && board_data_holder.turn() != color {}
+                db_instance.remove_moves_from(&game, board_data_holder.moves().count() as i32).unwrap();
+                println!();
+                println!("{}", &board_data_holder.board);
+            }
+            Command::REDO => {
+                while board_data_holder.redo().is_some() {
+                    save_last_move(&db_instance, &game, &board_data_holder);
+                    if board_data_holder.turn() == color { break }
+                }
+                println!();
+                println!("{}", &board_data_holder.board);
+            }
         }
     }
 }
@@ -0,0 +1,221 @@
+//! Monte Carlo tree search implementation of `MoveSearch`.
+//!
+//! The tree is kept in an arena of `MctsNode`s indexed by `usize`. Every iteration
+//! walks down from the root with UCT selection making moves on the controller,
+//! expands one untried move, estimates the reached position with a rollout and
+//! propagates the reward back. The controller is restored before the next iteration.
+//!
+//! Rewards are in `[0, 1]` and are stored in a node from the point of view of the
+//! side which made the move leading to it. A captured king is a terminal position.
+
+use std::time::{Duration, Instant};
+
+use crate::board_controller::{BoardController, PointInfo};
+use crate::evaluation::TaperedEvaluator;
+use crate::figure::{Rank, W_PAWN};
+use crate::movement::Move;
+use crate::score::{Evaluator, MoveSearch};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rollout {
+    /// Random playout of at most the given number of plies,
+    /// positions left unfinished are scored by evaluation.
+    PLAYOUT(u32),
+    /// Static evaluation of the expanded position.
+    EVALUATION,
+}
+
+pub struct MctsSearch {
+    pub iterations: u32,
+    pub time_limit: Option<Duration>,
+    pub exploration: f64,
+    pub rollout: Rollout,
+    pub seed: u64,
+    pub evaluator: Box<dyn Evaluator>,
+}
+
+impl Default for MctsSearch {
+    fn default() -> Self {
+        MctsSearch {
+            iterations: 20_000,
+            time_limit: Some(Duration::from_secs(5)),
+            exploration: std::f64::consts::SQRT_2,
+            rollout: Rollout::EVALUATION,
+            seed: 0x9E37_79B9_7F4A_7C15,
+            evaluator: Box::new(TaperedEvaluator::default()),
+        }
+    }
+}
+
+struct MctsNode {
+    movement: Option<Move>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    reward: f64,
+    terminal: bool,
+}
+
+impl MctsNode {
+    fn new(movement: Option<Move>, parent: Option<usize>, untried: Vec<Move>, terminal: bool) -> Self {
+        MctsNode { movement, parent, children: Vec::new(), untried, visits: 0, reward: 0.0, terminal }
+    }
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Scale of the logistic curve mapping evaluation scores to winning probabilities.
+const SIGMOID_SCALE: f64 = 4.0 * W_PAWN as f64;
+
+fn win_probability(score: i32) -> f64 {
+    1.0 / (1.0 + (-(score as f64) / SIGMOID_SCALE).exp())
+}
+
+fn score_of(probability: f64) -> i32 {
+    let p = probability.clamp(1e-6, 1.0 - 1e-6);
+    (- SIGMOID_SCALE * (1.0 / p - 1.0).ln()) as i32
+}
+
+fn is_king_capture(controller: &BoardController, movement: &Move) -> bool {
+    let f = controller.board.point(movement.to);
+    f.rank() == Rank::KING && f.color() == controller.enemy_color
+}
+
+fn untried_moves(controller: &BoardController) -> Vec<Move> {
+    controller.friend_movies().iter().copied().collect()
+}
+
+impl MctsSearch {
+    fn select_child(&self, tree: &[MctsNode], node: usize) -> usize {
+        let log_visits = (tree[node].visits as f64).ln();
+        *tree[node].children.iter()
+            .max_by(|a, b| {
+                self.uct(&tree[**a], log_visits).partial_cmp(&self.uct(&tree[**b], log_visits)).unwrap()
+            })
+            .expect("Fully expanded node should have children")
+    }
+
+    fn uct(&self, node: &MctsNode, parent_log_visits: f64) -> f64 {
+        let visits = node.visits as f64;
+        node.reward / visits + self.exploration * (parent_log_visits / visits).sqrt()
+    }
+
+    /// Reward of the position for the side which moved into it.
+    fn rollout(&self, controller: &mut BoardController, rng: &mut XorShift) -> f64 {
+        controller.position_counter += 1;
+        match self.rollout {
+            Rollout::EVALUATION => 1.0 - win_probability(self.evaluator.evaluate(controller)),
+            Rollout::PLAYOUT(max_plies) => {
+                let mut history: Vec<(PointInfo, PointInfo)> = Vec::new();
+                let mut winner_to_move = None;
+                for ply in 0..max_plies {
+                    let move_list = controller.friend_movies();
+                    if move_list.len() == 0 { break }
+
+                    let movement = *move_list.iter().nth(rng.below(move_list.len())).unwrap();
+                    let king_capture = is_king_capture(controller, &movement);
+                    history.push(controller.make_move(&movement));
+                    controller.pass_move_to_enemy();
+
+                    if king_capture {
+                        winner_to_move = Some(ply.is_multiple_of(2));
+                        break;
+                    }
+                }
+
+                let reward_to_move = match winner_to_move {
+                    Some(true) => 1.0,
+                    Some(false) => 0.0,
+                    None if history.len().is_multiple_of(2) => win_probability(self.evaluator.evaluate(controller)),
+                    None => 1.0 - win_probability(self.evaluator.evaluate(controller)),
+                };
+
+                while let Some(info) = history.pop() {
+                    controller.pass_move_to_enemy();
+                    controller.unmake_move(info);
+                }
+                1.0 - reward_to_move
+            }
+        }
+    }
+}
+
+impl MoveSearch for MctsSearch {
+    /// `depth` is not used, the search is bounded by `iterations` and `time_limit` instead.
+    fn find_best_move(&self, controller: &mut BoardController, _depth: i32) -> (i32, Option<Move>) {
+        self.evaluator.attach(controller);
+        let timer = Instant::now();
+        let mut rng = XorShift(self.seed.max(1));
+        let mut tree = vec![MctsNode::new(None, None, untried_moves(controller), false)];
+        let mut path: Vec<(PointInfo, PointInfo)> = Vec::new();
+
+        for _ in 0..self.iterations {
+            if self.time_limit.is_some_and(|limit| timer.elapsed() >= limit) { break }
+
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select_child(&tree, node);
+                path.push(controller.make_move(&tree[node].movement.unwrap()));
+                controller.pass_move_to_enemy();
+            }
+
+            if !tree[node].terminal && !tree[node].untried.is_empty() {
+                let i = rng.below(tree[node].untried.len());
+                let movement = tree[node].untried.swap_remove(i);
+                let terminal = is_king_capture(controller, &movement);
+
+                path.push(controller.make_move(&movement));
+                controller.pass_move_to_enemy();
+
+                let untried = if terminal { Vec::new() } else { untried_moves(controller) };
+                tree.push(MctsNode::new(Some(movement), Some(node), untried, terminal));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            let mut reward = if tree[node].terminal {
+                1.0
+            } else if tree[node].untried.is_empty() && tree[node].children.is_empty() {
+                0.5
+            } else {
+                self.rollout(controller, &mut rng)
+            };
+
+            let mut cur = Some(node);
+            while let Some(i) = cur {
+                tree[i].visits += 1;
+                tree[i].reward += reward;
+                reward = 1.0 - reward;
+                cur = tree[i].parent;
+            }
+
+            while let Some(info) = path.pop() {
+                controller.pass_move_to_enemy();
+                controller.unmake_move(info);
+            }
+        }
+
+        match tree[0].children.iter().max_by_key(|c| tree[**c].visits) {
+            None => (score_of(0.5), tree[0].untried.first().copied()),
+            Some(best) => {
+                let best = &tree[*best];
+                (score_of(best.reward / best.visits as f64), best.movement)
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Mobility and piece activity evaluation.
+//!
+//! Mobility counts the cells a figure attacks which are neither occupied by a friend figure
+//! nor attacked by an enemy pawn. Activity bonuses cover rooks on open files and the seventh
+//! rank, the bishop pair, knight outposts and connected rooks. The constants are the defaults
+//! of `EvalParams`, values are in centipawns, see `PST_SCALE`.
+
+use crate::board::Board;
+use crate::eval_params::EvalParams;
+use crate::evaluation::TaperedScore;
+use crate::figure::{Color, Rank};
+use crate::figure_list::FigurePointList;
+use crate::king_safety::{file_has_pawn, has_pawn};
+use crate::movement::for_each_attack;
+use crate::point::Point;
+use crate::pst::PST_SCALE;
+
+/// Bonus per safe attacked cell, indexed by `Rank`.
+pub const MOBILITY: [TaperedScore; 8] = [
+    TaperedScore::new(0, 0),
+    TaperedScore::new(0, 0),
+    TaperedScore::new(1, 2),
+    TaperedScore::new(2, 4),
+    TaperedScore::new(5, 5),
+    TaperedScore::new(4, 4),
+    TaperedScore::new(0, 0),
+    TaperedScore::new(0, 0),
+];
+pub const ROOK_OPEN_FILE: TaperedScore = TaperedScore::new(20, 10);
+pub const ROOK_HALF_OPEN_FILE: TaperedScore = TaperedScore::new(10, 5);
+pub const ROOK_SEVENTH_RANK: TaperedScore = TaperedScore::new(20, 30);
+pub const CONNECTED_ROOKS: TaperedScore = TaperedScore::new(15, 5);
+pub const BISHOP_PAIR: TaperedScore = TaperedScore::new(30, 50);
+pub const KNIGHT_OUTPOST: TaperedScore = TaperedScore::new(20, 10);
+
+fn relative_rank(p: Point, color: Color) -> i8 {
+    if color == Color::WHITE { p.y() } else { 7 - p.y() }
+}
+
+/// Cells attacked by the pawns in `list`, indexed by `x * 8 + y`.
+fn pawn_attacks<B: Board>(board: &B, list: &FigurePointList) -> [bool; 64] {
+    let mut attacked = [false; 64];
+    list.iter()
+        .filter(|p| board.point(*p).rank() == Rank::PAWN)
+        .for_each(|p| for_each_attack(board, p, |to_p| attacked[(to_p.x() * 8 + to_p.y()) as usize] = true));
+    attacked
+}
+
+/// A knight on the enemy half, defended by a friend pawn and out of reach of enemy pawns.
+fn is_outpost<B: Board>(board: &B, p: Point, color: Color) -> bool {
+    let rank = relative_rank(p, color);
+    if !(3..=5).contains(&rank) { return false }
+
+    let backward: i8 = if color == Color::WHITE { -1 } else { 1 };
+    let defended = [p.x() - 1, p.x() + 1].iter()
+        .any(|x| (0..8).contains(x) && has_pawn(board, *x, p.y() + backward, color));
+    let attackable = [p.x() - 1, p.x() + 1].iter()
+        .filter(|x| (0..8).contains(*x))
+        .any(|x| (0..8).filter(|y| relative_rank(Point::new(*x, *y), color) > rank)
+            .any(|y| has_pawn(board, *x, y, color.invert())));
+    defended && !attackable
+}
+
+/// Mobility and activity of the side whose figures are in `friend_list`.
+pub fn mobility<B: Board>(params: &EvalParams, board: &B, friend_list: &FigurePointList, enemy_list: &FigurePointList) -> TaperedScore {
+    let color = match friend_list.iter().next() {
+        None => return TaperedScore::default(),
+        Some(p) => board.point(p).color(),
+    };
+    let enemy_pawn_attacks = pawn_attacks(board, enemy_list);
+
+    let mut score = TaperedScore::default();
+    let mut bishops = 0;
+    let mut connected_rooks = false;
+    for p in friend_list.iter() {
+        let rank = board.point(p).rank();
+        let mut safe_cells = 0;
+        for_each_attack(board, p, |to_p| {
+            let f = board.point(to_p);
+            if rank == Rank::ROOK && f.rank() == Rank::ROOK && f.color() == color {
+                connected_rooks = true;
+            }
+            if (f.rank() == Rank::NONE || f.color() != color) && !enemy_pawn_attacks[(to_p.x() * 8 + to_p.y()) as usize] {
+                safe_cells += 1;
+            }
+        });
+        score += params.mobility[rank as usize] * safe_cells;
+
+        match rank {
+            Rank::ROOK => {
+                if !file_has_pawn(board, p.x(), color) {
+                    score += if file_has_pawn(board, p.x(), color.invert()) { params.rook_half_open_file } else { params.rook_open_file };
+                }
+                if relative_rank(p, color) == 6 { score += params.rook_seventh_rank }
+            }
+            Rank::BISHOP => bishops += 1,
+            Rank::KNIGHT if is_outpost(board, p, color) => score += params.knight_outpost,
+            _ => {}
+        }
+    }
+    if bishops >= 2 { score += params.bishop_pair }
+    if connected_rooks { score += params.connected_rooks }
+
+    score * PST_SCALE
+}
@@ -2,12 +2,14 @@
 
 use std::fmt::{Display, Formatter};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::slice::Iter;
 
-use crate::board::ByteBoard;
+use crate::bitboard::{square, square_point};
+use crate::board::{Board, ByteBoard};
 use crate::figure::{Color, Rank, Figure};
 use crate::figure::Rank::OUT;
-use crate::figure_list::FigurePointList;
+use crate::figure_list::{FigurePointList, PieceList};
 use crate::point::Point;
 use std::mem::MaybeUninit;
 
@@ -19,14 +21,97 @@ pub enum MoveType {
     TRANSFORM,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+/// A move together with what it does to the board.
+///
+/// `captured` and `flags` describe the position the move is made in, they are filled by
+/// `MoveGenerator` so consumers don't have to look at the board again. Movies are equal when their
+/// packed forms are, the captured figure is left out of both.
+#[derive(Debug, Copy, Clone)]
 pub struct Move {
     pub from: Point,
     pub to: Point,
-    pub m_type: MoveType
+    pub m_type: MoveType,
+    /// Rank of the taken figure, `NONE` for a quiet move.
+    pub captured: Rank,
+    /// Rank a `TRANSFORM` pawn becomes, `NONE` for the other move types.
+    pub promotion: Rank,
+    pub flags: u8,
 }
 
+/// Promotion ranks in the order of the packed move kinds.
+const PROMOTIONS: [Rank; 4] = [Rank::KNIGHT, Rank::BISHOP, Rank::ROOK, Rank::QUEEN];
+
 impl Move {
+    /// The king move of a castling, the rook follows it.
+    pub const CASTLE: u8 = 1;
+    /// A pawn taking the pawn which has just passed it, `captured` is that pawn.
+    pub const EN_PASSANT: u8 = 2;
+
+    /// Move of `m_type`, a `TRANSFORM` makes a queen.
+    pub fn new(from: Point, to: Point, m_type: MoveType) -> Self {
+        let promotion = if m_type == MoveType::TRANSFORM { Rank::QUEEN } else { Rank::NONE };
+        Move { from, to, m_type, captured: Rank::NONE, promotion, flags: 0 }
+    }
+
+    pub fn with_capture(self, captured: Rank) -> Self {
+        Move { captured, ..self }
+    }
+
+    pub fn with_promotion(self, promotion: Rank) -> Self {
+        Move { m_type: MoveType::TRANSFORM, promotion, ..self }
+    }
+
+    pub fn with_flags(self, flags: u8) -> Self {
+        Move { flags, ..self }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.captured != Rank::NONE
+    }
+
+    pub fn is_castle(&self) -> bool {
+        self.flags & Move::CASTLE != 0
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags & Move::EN_PASSANT != 0
+    }
+
+    /// Rank the moving pawn becomes, a queen when a `TRANSFORM` doesn't say.
+    pub fn promoted_rank(&self) -> Rank {
+        if self.promotion == Rank::NONE { Rank::QUEEN } else { self.promotion }
+    }
+
+    /// 16-bit form: origin in bits 0..6 and destination in 6..12, cells numbered `y * 8 + x`,
+    /// then the kind in 12..16: 0 simple, 1 swap, 2 castle, 3 en passant, 4 to 7 a transform to
+    /// a knight, bishop, rook or queen. No move packs to 0.
+    pub fn pack(&self) -> u16 {
+        let kind = match self.m_type {
+            MoveType::SIMPLE if self.is_castle() => 2,
+            MoveType::SIMPLE if self.is_en_passant() => 3,
+            MoveType::SIMPLE => 0,
+            MoveType::SWAP => 1,
+            MoveType::TRANSFORM => 4 + PROMOTIONS.iter().position(|r| *r == self.promoted_rank()).unwrap_or(3) as u16,
+        };
+        (kind << 12) | ((square(self.to) as u16) << 6) | square(self.from) as u16
+    }
+
+    /// Move of a `pack` result, `None` for 0. The captured figure is not kept, en passant
+    /// excepted, so it is `NONE`.
+    pub fn unpack(bits: u16) -> Option<Self> {
+        if bits == 0 { return None }
+
+        let from = square_point((bits & 63) as usize);
+        let to = square_point(((bits >> 6) & 63) as usize);
+        Some(match bits >> 12 {
+            0 => Move::new(from, to, MoveType::SIMPLE),
+            1 => Move::new(from, to, MoveType::SWAP),
+            2 => Move::new(from, to, MoveType::SIMPLE).with_flags(Move::CASTLE),
+            3 => Move::new(from, to, MoveType::SIMPLE).with_flags(Move::EN_PASSANT).with_capture(Rank::PAWN),
+            kind => Move::new(from, to, MoveType::TRANSFORM).with_promotion(PROMOTIONS[(kind as usize - 4) % 4]),
+        })
+    }
+
     pub fn from_string(str: &str) -> Result<Self, fmt::Error> {
         if str.len() != 4 { return Err(fmt::Error) }
 
@@ -38,9 +123,23 @@ impl Move {
     }
 }
 
+impl PartialEq for Move {
+    fn eq(&self, other: &Self) -> bool {
+        self.pack() == other.pack()
+    }
+}
+
+impl Eq for Move {}
+
+impl Hash for Move {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pack().hash(state)
+    }
+}
+
 impl Default for Move {
     fn default() -> Self {
-        Move { from: Default::default(), to: Default::default(), m_type: MoveType::SIMPLE }
+        Move::new(Default::default(), Default::default(), MoveType::SIMPLE)
     }
 }
 
@@ -84,7 +183,15 @@ impl MoveList {
         self.buffer[..self.len].iter()
     }
 
-    pub fn sort_by<F: FnMut(Point, Figure) -> i32>(&mut self, board: &ByteBoard, mut positional_fn: F) {
+    /// Moves `m` to the head of the list keeping the order of the other moves,
+    /// does nothing if the list has no such move.
+    pub fn move_to_front(&mut self, m: &Move) {
+        if let Some(i) = self.buffer[..self.len].iter().position(|e| e == m) {
+            self.buffer[..=i].rotate_right(1);
+        }
+    }
+
+    pub fn sort_by<B: Board, F: FnMut(Point, Figure) -> i32>(&mut self, board: &B, mut positional_fn: F) {
         self.buffer[0..self.len].sort_by(|a, b| {
             let a_f = *board.point(a.from);
             let b_f = *board.point(b.from);
@@ -106,9 +213,9 @@ impl Default for MoveList {
     }
 }
 
-pub struct MoveGenerator<'a> {
-    pub board: &'a ByteBoard,
-    pub figures: &'a FigurePointList,
+pub struct MoveGenerator<'a, B: Board = ByteBoard, L: PieceList = FigurePointList> {
+    pub board: &'a B,
+    pub figures: &'a L,
 }
 
 static KING_MOVES_X: [i8; 8] = [ 0, 1, 1, 0, -1, -1, -1, 1 ];
@@ -123,7 +230,7 @@ static ROOK_DIRECTIONS_Y: [i8; 4] = [ 1, 0, 0, -1 ];
 static BISHOP_DIRECTIONS_X: [i8; 4] = [ 1, -1, 1, -1 ];
 static BISHOP_DIRECTIONS_Y: [i8; 4] = [ 1, 1, -1, -1 ];
 
-impl<'a> Generator for MoveGenerator<'a> {
+impl<'a, B: Board, L: PieceList> Generator for MoveGenerator<'a, B, L> {
     fn fill(&self, move_list: &mut MoveList) {
         // println!("{}\n", self.board);
         move_list.clear();
@@ -133,29 +240,22 @@ impl<'a> Generator for MoveGenerator<'a> {
     }
 }
 
-impl<'a> MoveGenerator<'a> {
-    pub fn new(board: &'a ByteBoard, figures: &'a FigurePointList) -> Self {
+impl<'a, B: Board, L: PieceList> MoveGenerator<'a, B, L> {
+    pub fn new(board: &'a B, figures: &'a L) -> Self {
         MoveGenerator { board, figures }
     }
 
     pub fn fill_for_figure(&self, p: Point, move_list: &mut MoveList) {
         let f =  self.board.point(p);
         match f.rank() {
-            Rank::KING => {
-                self.generate_moves(p, &KING_MOVES_X, &KING_MOVES_Y, move_list);
-            }
-            Rank::QUEEN => {
-                self.generate_directions_moves(p, &ROOK_DIRECTIONS_X, &ROOK_DIRECTIONS_Y, move_list);
-                self.generate_directions_moves(p, &BISHOP_DIRECTIONS_X, &BISHOP_DIRECTIONS_Y, move_list);
-            }
-            Rank::ROOK => {
-                self.generate_directions_moves(p, &ROOK_DIRECTIONS_X, &ROOK_DIRECTIONS_Y, move_list);
-            }
-            Rank::BISHOP => {
-                self.generate_directions_moves(p, &BISHOP_DIRECTIONS_X, &BISHOP_DIRECTIONS_Y, move_list);
-            }
-            Rank::KNIGHT => {
-                self.generate_moves(p, &KNIGHT_MOVES_X, &KNIGHT_MOVES_Y, move_list);
+            Rank::KING | Rank::QUEEN | Rank::ROOK | Rank::BISHOP | Rank::KNIGHT => {
+                let f_color = f.color();
+                self.board.for_each_attack(p, |to_p| {
+                    let target = self.board.point(to_p);
+                    if target.color() != f_color {
+                        move_list.push(Move::new(p, to_p, MoveType::SIMPLE).with_capture(target.rank()));
+                    }
+                });
             }
             Rank::PAWN => {
                 let eat_color;
@@ -172,23 +272,25 @@ impl<'a> MoveGenerator<'a> {
                 };
 
                 let eat_p = p + Point::new(1, mult);
-                if self.board.point(eat_p).color() == eat_color { move_list.push(Move { from: p, to: eat_p, m_type: MoveType::SIMPLE }) }
+                let target = self.board.point(eat_p);
+                if target.color() == eat_color { move_list.push(Move::new(p, eat_p, MoveType::SIMPLE).with_capture(target.rank())) }
 
                 let eat_p = p + Point::new(-1, mult);
-                if self.board.point(eat_p).color() == eat_color { move_list.push(Move { from: p, to: eat_p, m_type: MoveType::SIMPLE }) }
+                let target = self.board.point(eat_p);
+                if target.color() == eat_color { move_list.push(Move::new(p, eat_p, MoveType::SIMPLE).with_capture(target.rank())) }
 
                 let eat_p = p + Point::new(0, mult);
                 if self.board.point(eat_p).rank() == Rank::NONE {
                     if eat_p.y() == 7 || eat_p.y() == 0 {
-                        move_list.push(Move { from: p, to: eat_p, m_type: MoveType::TRANSFORM });
+                        move_list.push(Move::new(p, eat_p, MoveType::TRANSFORM));
                     } else {
-                        move_list.push(Move { from: p, to: eat_p, m_type: MoveType::SIMPLE });
+                        move_list.push(Move::new(p, eat_p, MoveType::SIMPLE));
                     }
 
                     if p.y() == 1i8 && mult == 1 || p.y() == 6i8 && mult == -1 {
                         let eat_p = p + Point::new(0, mult * 2);
                         if self.board.point(eat_p).rank() == Rank::NONE {
-                            move_list.push(Move { from: p, to:  eat_p, m_type: MoveType::SIMPLE })
+                            move_list.push(Move::new(p, eat_p, MoveType::SIMPLE))
                         }
                     }
                 }
@@ -202,6 +304,11 @@ impl<'a> MoveGenerator<'a> {
         // }
     }
 
+    /// Cells attacked by the generator's figures.
+    pub fn attack_map(&self) -> AttackMap {
+        AttackMap::with_figures(self.board, self.figures.iter())
+    }
+
     pub fn move_if_not_out(&self, p: Point, dx: i8, dy: i8) -> Option<Point> {
         let p_move = p + Point::new(dx, dy);
         if self.board.point(p_move).rank() != OUT {
@@ -210,37 +317,116 @@ impl<'a> MoveGenerator<'a> {
             None
         }
     }
+}
 
-    fn generate_moves(&self, p: Point, movies_x: &[i8; 8], movies_y: &[i8; 8], move_list: &mut MoveList) {
-        let f_color = self.board.point(p).color();
-        movies_x.iter()
-            .zip(movies_y.iter())
-            .filter_map(|dp| self.move_if_not_out(p, *dp.0, *dp.1))
-            .filter(|to_p| f_color != self.board.point(*to_p).color())
-            .for_each(|to_p| {
-                move_list.push(Move { from: p, to: to_p, m_type: MoveType::SIMPLE });
-            });
-    }
-
-    fn generate_directions_moves(&self, p: Point, directions_x: &[i8; 4], directions_y: &[i8; 4], move_list: &mut MoveList) {
-        let f_color = self.board.point(p).color();
-        let enemy_color = f_color.invert();
-        directions_x.iter()
-            .zip(directions_y.iter())
-            .for_each(|d| {
-                let mut to_p = p;
-                loop {
-                    match self.move_if_not_out(to_p, *d.0, *d.1) {
-                        None => break,
-                        Some(new_to_p) => {
-                            let to_color = self.board.point(new_to_p).color();
-                            if to_color == f_color { break; }
-                            move_list.push(Move { from: p, to: new_to_p, m_type: MoveType::SIMPLE });
-                            if to_color == enemy_color { break; }
-                            to_p = new_to_p
-                        }
-                    }
-                }
-            });
+fn for_each_step<B: Board, F: FnMut(Point)>(board: &B, p: Point, movies_x: &[i8], movies_y: &[i8], f: &mut F) {
+    movies_x.iter()
+        .zip(movies_y.iter())
+        .map(|(dx, dy)| p + Point::new(*dx, *dy))
+        .filter(|to_p| board.point(*to_p).rank() != OUT)
+        .for_each(f);
+}
+
+fn for_each_ray<B: Board, F: FnMut(Point)>(board: &B, p: Point, directions_x: &[i8], directions_y: &[i8], f: &mut F) {
+    directions_x.iter()
+        .zip(directions_y.iter())
+        .for_each(|(dx, dy)| {
+            let mut to_p = p + Point::new(*dx, *dy);
+            loop {
+                let rank = board.point(to_p).rank();
+                if rank == OUT { break }
+                f(to_p);
+                if rank != Rank::NONE { break }
+                to_p = to_p + Point::new(*dx, *dy);
+            }
+        });
+}
+
+/// Calls `f` for every cell attacked by the figure standing on `p`.
+///
+/// Unlike movies, attacks include cells occupied by figures of the same color (they are
+/// defended), sliding rays stop at the first occupied cell, and a pawn attacks only its two
+/// forward diagonals. Does nothing for an empty cell.
+pub fn for_each_attack<B: Board, F: FnMut(Point)>(board: &B, p: Point, f: F) {
+    board.for_each_attack(p, f)
+}
+
+/// Cells of the `color` figures attacking `p`, see `Board::attackers`, found by walking from `p`
+/// the way each rank attacks. The default of `Board::attackers`.
+pub fn walk_attackers<B: Board>(board: &B, p: Point, color: Color) -> u64 {
+    let mut attackers = 0u64;
+    let mut add = |to: Point, ranks: &[Rank]| {
+        let f = board.point(to);
+        if f.color() == color && ranks.contains(&f.rank()) { attackers |= 1 << square(to) }
+    };
+    for_each_step(board, p, &KING_MOVES_X, &KING_MOVES_Y, &mut |to| add(to, &[Rank::KING]));
+    for_each_step(board, p, &KNIGHT_MOVES_X, &KNIGHT_MOVES_Y, &mut |to| add(to, &[Rank::KNIGHT]));
+    for_each_ray(board, p, &ROOK_DIRECTIONS_X, &ROOK_DIRECTIONS_Y, &mut |to| add(to, &[Rank::ROOK, Rank::QUEEN]));
+    for_each_ray(board, p, &BISHOP_DIRECTIONS_X, &BISHOP_DIRECTIONS_Y, &mut |to| add(to, &[Rank::BISHOP, Rank::QUEEN]));
+    let dy = if color == Color::WHITE { -1 } else { 1 };
+    for_each_step(board, p, &[1, -1], &[dy, dy], &mut |to| add(to, &[Rank::PAWN]));
+    attackers
+}
+
+/// Numbers of figures attacking the figure on `p`: of the other color and of its own, its defenders.
+pub fn attack_counts<B: Board>(board: &B, p: Point) -> (u32, u32) {
+    let color = board.point(p).color();
+    (board.attackers(p, color.invert()).count_ones(), board.attackers(p, color).count_ones())
+}
+
+/// Cells attacked by the figures of one side with the number of attackers of each.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AttackMap {
+    counts: [u8; 64],
+}
+
+impl AttackMap {
+    /// Attacks of every `color` figure of `board`.
+    pub fn new<B: Board>(board: &B, color: Color) -> Self {
+        let points = board.cell_iter().filter(|(_, f)| f.color() == color).map(|(p, _)| p);
+        AttackMap::with_figures(board, points)
+    }
+
+    /// Attacks of the figures standing on `points`.
+    pub fn with_figures<B: Board, I: Iterator<Item = Point>>(board: &B, points: I) -> Self {
+        let mut map = AttackMap { counts: [0; 64] };
+        points.for_each(|p| board.for_each_attack(p, |to| map.counts[square(to)] += 1));
+        map
+    }
+
+    /// Number of figures attacking `point`.
+    pub fn count(&self, point: Point) -> u8 {
+        self.counts[square(point)]
+    }
+
+    pub fn is_attacked(&self, point: Point) -> bool {
+        self.count(point) > 0
+    }
+
+    /// Attacked cells as bits `y * 8 + x`.
+    pub fn mask(&self) -> u64 {
+        self.counts.iter().enumerate()
+            .filter(|(_, c)| **c > 0)
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    }
+}
+
+/// `for_each_attack` walking the rays cell by cell, the default of `Board::for_each_attack`.
+pub fn walk_attacks<B: Board, F: FnMut(Point)>(board: &B, p: Point, mut f: F) {
+    let figure = board.point(p);
+    match figure.rank() {
+        Rank::KING => for_each_step(board, p, &KING_MOVES_X, &KING_MOVES_Y, &mut f),
+        Rank::KNIGHT => for_each_step(board, p, &KNIGHT_MOVES_X, &KNIGHT_MOVES_Y, &mut f),
+        Rank::QUEEN => {
+            for_each_ray(board, p, &ROOK_DIRECTIONS_X, &ROOK_DIRECTIONS_Y, &mut f);
+            for_each_ray(board, p, &BISHOP_DIRECTIONS_X, &BISHOP_DIRECTIONS_Y, &mut f);
+        }
+        Rank::ROOK => for_each_ray(board, p, &ROOK_DIRECTIONS_X, &ROOK_DIRECTIONS_Y, &mut f),
+        Rank::BISHOP => for_each_ray(board, p, &BISHOP_DIRECTIONS_X, &BISHOP_DIRECTIONS_Y, &mut f),
+        Rank::PAWN => {
+            let dy = if figure.color() == Color::WHITE { 1 } else { -1 };
+            for_each_step(board, p, &[1, -1], &[dy, dy], &mut f);
+        }
+        Rank::NONE | Rank::OUT => {}
     }
 }
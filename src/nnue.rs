@@ -0,0 +1,250 @@
+//! Small NNUE-style neural network evaluation.
+//!
+//! The network has 768 piece-square input features, one per figure kind, color and cell, seen
+//! from the point of view of each side: the perspective's own figures come first and black looks
+//! at the board with ranks mirrored. A shared hidden layer turns the features of both perspectives
+//! into two accumulators, both go through a clipped ReLU and the output neuron reads the side to
+//! move accumulator first. Weights are quantised to `i16`: the hidden layer by `QA`, the output
+//! layer by `QB`, the output is scaled by `OUTPUT_SCALE` to centipawns.
+//!
+//! Accumulators only depend on the figures on the board, so `BoardController` keeps an attached
+//! `Accumulator` up to date in `make_move` and `unmake_move` by removing and adding the features
+//! of the two changed cells instead of summing all of them again.
+//!
+//! The network does not know the king is priceless, `NnueEvaluator` adds `W_KING` for every king
+//! on the board so that the search still sees a captured king as the lost game.
+//!
+//! Weights file layout, all numbers little-endian: the `NNUE_MAGIC` bytes, a `u32` hidden layer
+//! size `H`, `768 * H` feature weights (feature major), `H` hidden biases, `2 * H` output weights
+//! as `i16`s and an `i32` output bias.
+
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::sync::Arc;
+
+use crate::board::Board;
+use crate::board_controller::BoardController;
+use crate::figure::{Color, Figure, Rank, W_KING};
+use crate::figure_list::FigurePointList;
+use crate::point::Point;
+use crate::pst::PST_SCALE;
+use crate::score::Evaluator;
+
+pub const FEATURES: usize = 768;
+pub const QA: i32 = 255;
+pub const QB: i32 = 64;
+pub const OUTPUT_SCALE: i32 = 400;
+pub const NNUE_MAGIC: &[u8; 4] = b"NNUE";
+
+/// Index of `figure` standing on `point` among the input features of `perspective`.
+pub fn feature_index(perspective: Color, point: Point, figure: Figure) -> usize {
+    let cell = point.y() as usize * 8 + (7 - point.x() as usize);
+    let cell = if perspective == Color::BLACK { cell ^ 56 } else { cell };
+    let side = if figure.color() == perspective { 0 } else { 1 };
+    (side * 6 + figure.rank() as usize - Rank::KING as usize) * 64 + cell
+}
+
+fn is_figure(figure: Figure) -> bool {
+    figure.rank() != Rank::NONE && figure.rank() != Rank::OUT
+}
+
+fn perspective_index(color: Color) -> usize {
+    if color == Color::BLACK { 1 } else { 0 }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_bias: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+fn read_i16s<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<i16>> {
+    let mut bytes = vec![0u8; count * 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect())
+}
+
+fn write_i16s<W: Write>(writer: &mut W, values: &[i16]) -> io::Result<()> {
+    values.iter().try_for_each(|v| writer.write_all(&v.to_le_bytes()))
+}
+
+impl Network {
+    /// Creates a network from already quantised weights, see the module docs for the layout.
+    pub fn new(hidden: usize, feature_weights: Vec<i16>, feature_bias: Vec<i16>, output_weights: Vec<i16>, output_bias: i32) -> Self {
+        assert_eq!(feature_weights.len(), FEATURES * hidden, "Wrong feature weights size");
+        assert_eq!(feature_bias.len(), hidden, "Wrong feature bias size");
+        assert_eq!(output_weights.len(), 2 * hidden, "Wrong output weights size");
+        Network { hidden, feature_weights, feature_bias, output_weights, output_bias }
+    }
+
+    /// Network with small pseudo-random weights, a starting point for training.
+    pub fn random(hidden: usize, seed: u64) -> Self {
+        let mut state = seed.max(1);
+        let mut next = |range: i16| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (2 * range as u64 + 1)) as i16 - range
+        };
+        let feature_weights = (0..FEATURES * hidden).map(|_| next(16)).collect();
+        let feature_bias = (0..hidden).map(|_| next(16)).collect();
+        let output_weights = (0..2 * hidden).map(|_| next(64)).collect();
+        Network::new(hidden, feature_weights, feature_bias, output_weights, 0)
+    }
+
+    pub fn hidden(&self) -> usize {
+        self.hidden
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != NNUE_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a network weights file"));
+        }
+
+        let mut number = [0u8; 4];
+        reader.read_exact(&mut number)?;
+        let hidden = u32::from_le_bytes(number) as usize;
+        if hidden == 0 || hidden > 4096 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported hidden layer size"));
+        }
+
+        let feature_weights = read_i16s(reader, FEATURES * hidden)?;
+        let feature_bias = read_i16s(reader, hidden)?;
+        let output_weights = read_i16s(reader, 2 * hidden)?;
+        reader.read_exact(&mut number)?;
+        Ok(Network::new(hidden, feature_weights, feature_bias, output_weights, i32::from_le_bytes(number)))
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(NNUE_MAGIC)?;
+        writer.write_all(&(self.hidden as u32).to_le_bytes())?;
+        write_i16s(writer, &self.feature_weights)?;
+        write_i16s(writer, &self.feature_bias)?;
+        write_i16s(writer, &self.output_weights)?;
+        writer.write_all(&self.output_bias.to_le_bytes())
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        Network::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    fn feature(&self, index: usize) -> &[i16] {
+        &self.feature_weights[index * self.hidden..(index + 1) * self.hidden]
+    }
+
+    /// Accumulators of both perspectives summed over every figure of `board`.
+    fn refresh<B: Board>(&self, board: &B) -> [Vec<i32>; 2] {
+        let mut values = [Color::WHITE, Color::BLACK].map(|_| self.feature_bias.iter().map(|b| *b as i32).collect::<Vec<_>>());
+        board.cell_iter()
+            .filter(|(_, f)| is_figure(**f))
+            .for_each(|(p, f)| self.update(&mut values, p, *f, 1));
+        values
+    }
+
+    fn update(&self, values: &mut [Vec<i32>; 2], point: Point, figure: Figure, sign: i32) {
+        for perspective in [Color::WHITE, Color::BLACK] {
+            let weights = self.feature(feature_index(perspective, point, figure));
+            values[perspective_index(perspective)].iter_mut()
+                .zip(weights)
+                .for_each(|(v, w)| *v += sign * *w as i32);
+        }
+    }
+
+    /// Output for the accumulators of the side to move and of its opponent, in `Figure::weight` units.
+    fn output(&self, us: &[i32], them: &[i32]) -> i32 {
+        let sum: i64 = us.iter().chain(them.iter())
+            .zip(self.output_weights.iter())
+            .map(|(v, w)| (*v).clamp(0, QA) as i64 * *w as i64)
+            .sum::<i64>() + self.output_bias as i64;
+        (sum * OUTPUT_SCALE as i64 / (QA * QB) as i64) as i32 * PST_SCALE
+    }
+}
+
+/// Hidden layer values of a position for both perspectives, kept by `BoardController`.
+#[derive(Clone)]
+pub struct Accumulator {
+    network: Arc<Network>,
+    values: [Vec<i32>; 2],
+}
+
+impl Accumulator {
+    pub fn new<B: Board>(network: Arc<Network>, board: &B) -> Self {
+        let values = network.refresh(board);
+        Accumulator { network, values }
+    }
+
+    pub fn network(&self) -> &Arc<Network> {
+        &self.network
+    }
+
+    /// Accumulator values of `perspective`.
+    pub fn values(&self, perspective: Color) -> &[i32] {
+        &self.values[perspective_index(perspective)]
+    }
+
+    /// Updates the features of `point` after its figure changed from `old` to `new`.
+    pub fn replace(&mut self, point: Point, old: Figure, new: Figure) {
+        if old == new { return }
+        if is_figure(old) { self.network.update(&mut self.values, point, old, -1) }
+        if is_figure(new) { self.network.update(&mut self.values, point, new, 1) }
+    }
+}
+
+/// `Evaluator` running a `Network`. It attaches an `Accumulator` to the searched controller,
+/// positions of controllers without one are evaluated from scratch.
+pub struct NnueEvaluator {
+    network: Arc<Network>,
+}
+
+impl NnueEvaluator {
+    pub fn new(network: Network) -> Self {
+        NnueEvaluator { network: Arc::new(network) }
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        Ok(NnueEvaluator::new(Network::load(path)?))
+    }
+}
+
+fn king_material<B: Board>(board: &B, list: &FigurePointList) -> i32 {
+    if list.iter().any(|p| board.point(p).rank() == Rank::KING) { W_KING } else { 0 }
+}
+
+impl<B: Board> Evaluator<B> for NnueEvaluator {
+    fn evaluate(&self, controller: &BoardController<B>) -> i32 {
+        let (us, them) = (controller.friend_color(), controller.enemy_color());
+        let kings = king_material(controller.board(), controller.friend_list())
+            - king_material(controller.board(), controller.enemy_list());
+        let output = match controller.accumulator() {
+            Some(accumulator) if Arc::ptr_eq(accumulator.network(), &self.network) => {
+                self.network.output(accumulator.values(us), accumulator.values(them))
+            }
+            _ => {
+                let values = self.network.refresh(controller.board());
+                self.network.output(&values[perspective_index(us)], &values[perspective_index(them)])
+            }
+        };
+        output + kings
+    }
+
+    fn attach(&self, controller: &mut BoardController<B>) {
+        let attached = controller.accumulator().is_some_and(|a| Arc::ptr_eq(a.network(), &self.network));
+        if !attached {
+            let accumulator = Accumulator::new(self.network.clone(), controller.board());
+            controller.set_accumulator(Some(accumulator));
+        }
+    }
+}
+
@@ -0,0 +1,208 @@
+//! Pawn structure evaluation.
+//!
+//! Scores doubled, isolated and backward pawns, pawns defended by another pawn (chains)
+//! and passed pawns with a bonus growing with their rank. The terms depend on pawns only,
+//! so results are cached in a `PawnHashTable` keyed by `zobrist::pawn_hash`.
+//! The constants are the defaults of `EvalParams`, values are in centipawns, see `PST_SCALE`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::board::Board;
+use crate::eval_params::EvalParams;
+use crate::evaluation::TaperedScore;
+use crate::figure::{Color, Rank};
+use crate::pst::PST_SCALE;
+use crate::zobrist;
+
+pub const DOUBLED: TaperedScore = TaperedScore { mg: -10, eg: -25 };
+pub const ISOLATED: TaperedScore = TaperedScore { mg: -15, eg: -20 };
+pub const BACKWARD: TaperedScore = TaperedScore { mg: -10, eg: -10 };
+pub const CHAIN: TaperedScore = TaperedScore { mg: 5, eg: 5 };
+/// Passed pawn bonus by rank counted from the pawn's own side.
+pub const PASSED: [TaperedScore; 8] = [
+    TaperedScore { mg: 0, eg: 0 },
+    TaperedScore { mg: 5, eg: 10 },
+    TaperedScore { mg: 5, eg: 15 },
+    TaperedScore { mg: 10, eg: 25 },
+    TaperedScore { mg: 20, eg: 45 },
+    TaperedScore { mg: 35, eg: 75 },
+    TaperedScore { mg: 60, eg: 120 },
+    TaperedScore { mg: 0, eg: 0 },
+];
+
+/// Pawn structure score of both sides.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PawnEval {
+    pub white: TaperedScore,
+    pub black: TaperedScore,
+}
+
+impl PawnEval {
+    pub fn side(&self, color: Color) -> TaperedScore {
+        if color == Color::BLACK { self.black } else { self.white }
+    }
+}
+
+/// Ranks (`Point::y`) occupied by the pawns of one side, a bit per rank for every file.
+struct PawnFiles([u8; 8]);
+
+impl PawnFiles {
+    fn new<B: Board>(board: &B, color: Color) -> Self {
+        let mut files = [0u8; 8];
+        board.cell_iter()
+            .filter(|(_, f)| f.rank() == Rank::PAWN && f.color() == color)
+            .for_each(|(p, _)| files[p.x() as usize] |= 1 << p.y());
+        PawnFiles(files)
+    }
+
+    fn file(&self, x: i8) -> u8 {
+        if (0..8).contains(&x) { self.0[x as usize] } else { 0 }
+    }
+
+    fn adjacent(&self, x: i8) -> u8 {
+        self.file(x - 1) | self.file(x + 1)
+    }
+}
+
+/// Bits of ranks strictly in front of `y` for a pawn of `color`.
+fn ranks_ahead(y: i8, color: Color) -> u8 {
+    if color == Color::WHITE { !((2u16 << y) - 1) as u8 } else { ((1u16 << y) - 1) as u8 }
+}
+
+fn scale(score: TaperedScore) -> TaperedScore {
+    TaperedScore::new(score.mg * PST_SCALE, score.eg * PST_SCALE)
+}
+
+fn evaluate_side(params: &EvalParams, friend: &PawnFiles, enemy: &PawnFiles, color: Color) -> TaperedScore {
+    let forward: i8 = if color == Color::WHITE { 1 } else { -1 };
+    let mut score = TaperedScore::default();
+
+    for x in 0..8i8 {
+        let file = friend.file(x);
+        if file == 0 { continue }
+        if file.count_ones() > 1 {
+            score += scale(params.doubled) * (file.count_ones() as i32 - 1);
+        }
+
+        for y in (0..8i8).filter(|y| file & (1 << y) != 0) {
+            let ahead = ranks_ahead(y, color);
+            let isolated = friend.adjacent(x) == 0;
+
+            if isolated {
+                score += scale(params.isolated);
+            } else {
+                let supporters = friend.adjacent(x) & !ahead;
+                let stop = y + forward;
+                let stop_attacked = (0..8).contains(&(stop + forward))
+                    && enemy.adjacent(x) & (1 << (stop + forward)) != 0;
+                if supporters == 0 && stop_attacked {
+                    score += scale(params.backward);
+                }
+            }
+
+            let behind = y - forward;
+            if (0..8).contains(&behind) && friend.adjacent(x) & (1 << behind) != 0 {
+                score += scale(params.chain);
+            }
+
+            if (enemy.file(x) | enemy.adjacent(x)) & ahead == 0 {
+                let relative_rank = if color == Color::WHITE { y } else { 7 - y };
+                score += scale(params.passed[relative_rank as usize]);
+            }
+        }
+    }
+    score
+}
+
+/// Computes the pawn structure of `board` without the cache.
+pub fn evaluate_pawns<B: Board>(params: &EvalParams, board: &B) -> PawnEval {
+    let white = PawnFiles::new(board, Color::WHITE);
+    let black = PawnFiles::new(board, Color::BLACK);
+    PawnEval {
+        white: evaluate_side(params, &white, &black, Color::WHITE),
+        black: evaluate_side(params, &black, &white, Color::BLACK),
+    }
+}
+
+#[derive(Default)]
+struct PawnSlot {
+    key: AtomicU64,
+    white: AtomicU64,
+    black: AtomicU64,
+}
+
+/// Lock-free cache of `PawnEval`s, built like `TranspositionTable`: a slot is only
+/// accepted when its key XOR-ed with both halves of its data gives back the probed pawn hash.
+/// Each side takes a whole word, the middlegame score in the low half and the endgame one in
+/// the high half, so that tuned params of any size are cached as they are.
+pub struct PawnHashTable {
+    slots: Vec<PawnSlot>,
+}
+
+pub const DEFAULT_PAWN_TABLE_SIZE_KB: usize = 1024;
+
+fn pack(score: TaperedScore) -> u64 {
+    (score.mg as u32 as u64) | ((score.eg as u32 as u64) << 32)
+}
+
+fn unpack(data: u64) -> TaperedScore {
+    TaperedScore::new(data as u32 as i32, (data >> 32) as u32 as i32)
+}
+
+impl PawnHashTable {
+    /// Creates a table taking about `size_kb` kilobytes, rounded down to a power of two slots.
+    pub fn new(size_kb: usize) -> Self {
+        let fit = (size_kb.max(1) * 1024 / std::mem::size_of::<PawnSlot>()).max(1);
+        let slot_count = 1usize << (usize::BITS - 1 - fit.leading_zeros());
+        let mut slots = Vec::with_capacity(slot_count);
+        slots.resize_with(slot_count, PawnSlot::default);
+        PawnHashTable { slots }
+    }
+
+    fn slot(&self, hash: u64) -> &PawnSlot {
+        &self.slots[hash as usize & (self.slots.len() - 1)]
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<PawnEval> {
+        let slot = self.slot(hash);
+        let white = slot.white.load(Ordering::Relaxed);
+        let black = slot.black.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed);
+        if key ^ white ^ black != hash { return None }
+        Some(PawnEval { white: unpack(white), black: unpack(black) })
+    }
+
+    pub fn store(&self, hash: u64, eval: &PawnEval) {
+        let slot = self.slot(hash);
+        let (white, black) = (pack(eval.white), pack(eval.black));
+        slot.key.store(hash ^ white ^ black, Ordering::Relaxed);
+        slot.white.store(white, Ordering::Relaxed);
+        slot.black.store(black, Ordering::Relaxed);
+    }
+
+    /// Forgets every cached evaluation, needed before the table is used with other params.
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.white.store(0, Ordering::Relaxed);
+            slot.black.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Pawn structure of `board`, from the cache when it was already evaluated.
+    /// A table must be used with the same `params` until it is cleared.
+    pub fn evaluate<B: Board>(&self, params: &EvalParams, board: &B) -> PawnEval {
+        let hash = zobrist::pawn_hash(board);
+        if let Some(eval) = self.probe(hash) { return eval }
+
+        let eval = evaluate_pawns(params, board);
+        self.store(hash, &eval);
+        eval
+    }
+}
+
+impl Default for PawnHashTable {
+    fn default() -> Self {
+        PawnHashTable::new(DEFAULT_PAWN_TABLE_SIZE_KB)
+    }
+}
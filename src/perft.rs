@@ -0,0 +1,25 @@
+//! Perft, the number of legal move sequences of a given length, for checking move generation
+//! and measuring its speed. The rules are the ones of `MoveGenerator`: no castling, no en passant
+//! and promotion by a push only, so counts match the published ones only while those don't occur.
+
+use crate::board::Board;
+use crate::board_controller::BoardController;
+use crate::figure_list::PieceList;
+
+/// Number of legal move sequences of `depth` plies from the controller's position.
+pub fn perft<B: Board, L: PieceList>(controller: &mut BoardController<B, L>, depth: u32) -> u64 {
+    if depth == 0 { return 1 }
+
+    let movies = controller.legal_movies();
+    if depth == 1 { return movies.len() as u64 }
+
+    let mut nodes = 0;
+    for movement in movies.iter() {
+        let move_info = controller.make_move(movement);
+        controller.pass_move_to_enemy();
+        nodes += perft(controller, depth - 1);
+        controller.pass_move_to_enemy();
+        controller.unmake_move(move_info);
+    }
+    nodes
+}
@@ -0,0 +1,123 @@
+//! Validated positions.
+//!
+//! `ByteBoard::cell_mut` takes any figure anywhere, while move generation and search expect one
+//! king per side, at most sixteen figures per side (the capacity of the piece lists), no pawn on
+//! the first or last rank and the side which just moved not being left in check. `PositionBuilder`
+//! checks all of it before a position reaches the engine and tells which rule was broken.
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::board::{Board, ByteBoard};
+use crate::figure::{Color, Figure, Rank};
+use crate::point::Point;
+
+/// Most figures a side can have, see `FigurePointList`.
+pub const MAX_FIGURES: usize = 16;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum PositionError {
+    /// The text of the position can't be read.
+    FORMAT,
+    /// A figure placed out of the board.
+    OUTSIDE(Point),
+    /// A side with no king or several of them, and their number.
+    KINGS(Color, usize),
+    /// A side with more than `MAX_FIGURES` figures, and their number.
+    FIGURES(Color, usize),
+    /// A pawn on the first or last rank.
+    PAWN(Point),
+    /// The king of the side not to move is attacked.
+    CHECK(Color),
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::FORMAT => write!(f, "malformed position"),
+            PositionError::OUTSIDE(p) => write!(f, "figure out of the board at {:?}", p),
+            PositionError::KINGS(color, n) => write!(f, "{:?} side has {} kings instead of one", color, n),
+            PositionError::FIGURES(color, n) => write!(f, "{:?} side has {} figures, at most {} fit", color, n, MAX_FIGURES),
+            PositionError::PAWN(p) => write!(f, "pawn on the first or last rank at {}", p),
+            PositionError::CHECK(color) => write!(f, "{:?} side is in check but not to move", color),
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+impl From<PositionError> for fmt::Error {
+    fn from(_: PositionError) -> Self {
+        fmt::Error
+    }
+}
+
+/// Checks `board` with `color` to move against the rules of the module docs.
+pub fn validate<B: Board>(board: &B, color: Color) -> Result<(), PositionError> {
+    for side in [Color::WHITE, Color::BLACK] {
+        let figures = board.cell_iter().filter(|(_, f)| f.color() == side).count();
+        if figures > MAX_FIGURES { return Err(PositionError::FIGURES(side, figures)) }
+
+        let kings = board.cell_iter().filter(|(_, f)| f.color() == side && f.rank() == Rank::KING).count();
+        if kings != 1 { return Err(PositionError::KINGS(side, kings)) }
+    }
+
+    if let Some((p, _)) = board.cell_iter().find(|(p, f)| f.rank() == Rank::PAWN && (p.y() == 0 || p.y() == 7)) {
+        return Err(PositionError::PAWN(p));
+    }
+
+    let enemy = color.invert();
+    let king = board.cell_iter().find(|(_, f)| f.rank() == Rank::KING && f.color() == enemy);
+    if king.is_some_and(|(p, _)| board.is_square_attacked(p, color)) { return Err(PositionError::CHECK(enemy)) }
+
+    Ok(())
+}
+
+/// Builds a position figure by figure and validates it.
+#[derive(Debug, Clone)]
+pub struct PositionBuilder {
+    board: ByteBoard,
+    turn: Color,
+    outside: Option<Point>,
+}
+
+impl PositionBuilder {
+    /// Empty board, white to move.
+    pub fn new() -> Self {
+        PositionBuilder { board: ByteBoard::empty(), turn: Color::WHITE, outside: None }
+    }
+
+    /// Starts from the figures of `board`.
+    pub fn from_board(board: &ByteBoard) -> Self {
+        PositionBuilder { board: *board, ..PositionBuilder::new() }
+    }
+
+    /// Puts `figure` on `point`, replacing what stood there. An empty figure clears the cell.
+    pub fn with_figure(mut self, point: Point, figure: Figure) -> Self {
+        if (0..8).contains(&point.x()) && (0..8).contains(&point.y()) {
+            *self.board.point_mut(point) = figure;
+        } else if self.outside.is_none() {
+            self.outside = Some(point);
+        }
+        self
+    }
+
+    pub fn with_turn(mut self, color: Color) -> Self {
+        self.turn = color;
+        self
+    }
+
+    /// The board and the side to move, or the first broken rule.
+    pub fn build(&self) -> Result<(ByteBoard, Color), PositionError> {
+        if let Some(point) = self.outside { return Err(PositionError::OUTSIDE(point)) }
+        validate(&self.board, self.turn)?;
+        Ok((self.board, self.turn))
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        PositionBuilder::new()
+    }
+}
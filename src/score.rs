@@ -1,9 +1,17 @@
+use std::sync::{Arc, LazyLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::board::{Board, ByteBoard};
 use crate::board_controller::BoardController;
-use crate::figure::{Figure, W_INFINITY};
-use crate::movement::Move;
+use crate::evaluation::TaperedEvaluator;
+use crate::figure::{Color, Figure, W_INFINITY, W_PAWN};
+use crate::movement::{Move, MoveList};
 use crate::point::Point;
+use crate::tablebase::Tablebase;
+use crate::transposition::{Bound, TranspositionTable, TtEntry, DEFAULT_TT_SIZE_MB};
+use crate::zobrist;
 
-pub fn evaluate_score<T: Fn(Point, Figure) -> i32>(controller: &BoardController, eval_fn: T) -> i32 {
+pub fn evaluate_score<B: Board, T: Fn(Point, Figure) -> i32>(controller: &BoardController<B>, eval_fn: T) -> i32 {
     let friend_score: i32 = controller.friend_list.iter()
         .map(|p| eval_fn(p, *controller.board.point(p)))
         .sum();
@@ -21,15 +29,88 @@ pub fn material_fn(_: Point, f: Figure) -> i32 {
     f.weight()
 }
 
+/// Small bonus for advancing, counted from the figure's own side so that both colors are scored alike.
 pub fn simple_positional_fn(p: Point, f: Figure) -> i32 {
-    (unsafe { std::mem::transmute::<_, u8>(f.color()) } as i32 - 64) + p.y() as i32 * 8 + (8 - p.x() as i32)
+    let rank = if f.color() == Color::BLACK { 7 - p.y() as i32 } else { p.y() as i32 };
+    rank * 8 + (8 - p.x() as i32)
+}
+
+/// Static evaluation of a whole position, the leaf score of every search.
+///
+/// Implementations must score the position from the point of view of
+/// `controller.friend_color()`, the side to move, so that negating the score
+/// gives the opponent's view. Evaluators are shared between search threads.
+/// The board representation defaults to `ByteBoard`, which the searches use.
+pub trait Evaluator<B: Board = ByteBoard>: Send + Sync {
+    fn evaluate(&self, controller: &BoardController<B>) -> i32;
+
+    /// Called by searches on the controller before making any move on it, lets an evaluator
+    /// attach incremental state like `nnue::Accumulator`.
+    fn attach(&self, _controller: &mut BoardController<B>) {}
+}
+
+/// Material plus `simple_positional_fn`, the evaluation searches used before `Evaluator` existed.
+#[derive(Default, Copy, Clone)]
+pub struct SimpleEvaluator;
+
+impl<B: Board> Evaluator<B> for SimpleEvaluator {
+    fn evaluate(&self, controller: &BoardController<B>) -> i32 {
+        evaluate_score(controller, |p, f| {
+            material_fn(p, f) + simple_positional_fn(p, f)
+        })
+    }
+}
+
+static DEFAULT_EVALUATOR: LazyLock<TaperedEvaluator> = LazyLock::new(TaperedEvaluator::default);
+
+pub const DEFAULT_ASPIRATION_WINDOW: i32 = W_PAWN / 2;
+pub const DEFAULT_MAX_EXTENSION: i32 = 4;
+
+pub struct MinMaxSimpleSearch {
+    pub evaluator: Box<dyn Evaluator>,
+}
+
+impl MinMaxSimpleSearch {
+    pub fn with_evaluator<E: Evaluator + 'static>(evaluator: E) -> Self {
+        MinMaxSimpleSearch { evaluator: Box::new(evaluator) }
+    }
+}
+
+impl Default for MinMaxSimpleSearch {
+    fn default() -> Self {
+        MinMaxSimpleSearch::with_evaluator(TaperedEvaluator::default())
+    }
+}
+
+pub struct AlphaBetaSearch {
+    /// Half width of the aspiration window around the previous iteration score,
+    /// zero or less searches every iteration with the full window.
+    pub aspiration_window: i32,
+    pub tt_size_mb: usize,
+    /// Maximum number of plies check and single reply extensions may add to one line.
+    pub max_extension: i32,
+    pub evaluator: Box<dyn Evaluator>,
+    /// Endgame tables probed at the root and for every searched position, see `SearchContext`.
+    pub tablebase: Option<Arc<Tablebase>>,
 }
 
-#[derive(Default)]
-pub struct MinMaxSimpleSearch {}
+impl AlphaBetaSearch {
+    pub fn with_evaluator<E: Evaluator + 'static>(evaluator: E) -> Self {
+        AlphaBetaSearch {
+            aspiration_window: DEFAULT_ASPIRATION_WINDOW,
+            tt_size_mb: DEFAULT_TT_SIZE_MB,
+            max_extension: DEFAULT_MAX_EXTENSION,
+            evaluator: Box::new(evaluator),
+            tablebase: None,
+        }
+    }
+}
 
-#[derive(Default)]
-pub struct AlphaBetaSearch {}
+impl Default for AlphaBetaSearch {
+    fn default() -> Self {
+        AlphaBetaSearch::with_evaluator(TaperedEvaluator::default())
+    }
+}
 
 pub trait MoveSearch {
     fn find_best_move(&self, controller: &mut BoardController, depth: i32) -> (i32, Option<Move>);
@@ -37,13 +118,27 @@ pub trait MoveSearch {
 
 impl MoveSearch for MinMaxSimpleSearch {
     fn find_best_move(&self, controller: &mut BoardController, depth: i32) -> (i32, Option<Move>) {
-        min_max_simple(controller, depth)
+        self.evaluator.attach(controller);
+        min_max(self.evaluator.as_ref(), controller, depth)
     }
 }
 
 impl MoveSearch for AlphaBetaSearch {
     fn find_best_move(&self, controller: &mut BoardController, depth: i32) -> (i32, Option<Move>) {
-        alpha_betta(controller, depth, - W_INFINITY, W_INFINITY)
+        if let Some((score, movement)) = self.tablebase.as_ref().and_then(|tb| tb.best_move(controller)) {
+            return (score, Some(movement));
+        }
+
+        self.evaluator.attach(controller);
+        let tt = TranspositionTable::new(self.tt_size_mb);
+        let context = SearchContext {
+            tt: Some(&tt),
+            max_extension: self.max_extension,
+            evaluator: self.evaluator.as_ref(),
+            tablebase: self.tablebase.as_deref(),
+            ..SearchContext::default()
+        };
+        iterative_deepening(&context, controller, depth, self.aspiration_window)
     }
 }
 
@@ -82,16 +177,19 @@ impl MoveSearch for AlphaBetaSearch {
 ///
 /// * The function assumes `W_INFINITY` is a predefined constant representing a very large score, 
 ///   which serves as a placeholder for the best possible/worst possible evaluation.
-/// * `evaluate_score` is used to calculate the value of the board using a scoring function 
-///   that combines material and positional considerations.
+/// * `TaperedEvaluator` is used to calculate the value of the board using a scoring function 
+///   that combines material and piece-square tables, `min_max` accepts any other `Evaluator`.
 /// * Ties are broken by selecting the first move encountered with the same score.
 /// 
 pub fn min_max_simple(controller: &mut BoardController, depth: i32) -> (i32, Option<Move>) {
+    min_max(&*DEFAULT_EVALUATOR, controller, depth)
+}
+
+/// `min_max_simple` scoring leaf positions with `evaluator`.
+pub fn min_max(evaluator: &dyn Evaluator, controller: &mut BoardController, depth: i32) -> (i32, Option<Move>) {
     if depth <= 0 {
         controller.position_counter += 1;
-        return (evaluate_score(controller, |p, f| {
-            material_fn(p, f) + simple_positional_fn(p, f)
-        }), None);
+        return (evaluator.evaluate(controller), None);
     }
 
     // unsafe { println!("{:?}", (*friend_list.first).point); }
@@ -112,7 +210,7 @@ pub fn min_max_simple(controller: &mut BoardController, depth: i32) -> (i32, Opt
         // println!("{}", self.board);
         // println!();
 
-        let cur_score = - min_max_simple(controller, depth - 1).0;
+        let cur_score = - min_max(evaluator, controller, depth - 1).0;
 
         controller.pass_move_to_enemy();
         controller.unmake_move(move_info);
@@ -163,7 +261,7 @@ pub fn min_max_simple(controller: &mut BoardController, depth: i32) -> (i32, Opt
 ///
 /// 1. **Base Case**:
 ///    - If `depth <= 0`, the maximum search depth is reached. The function evaluates the current 
-///      board position using the `evaluate_score` function with a material and positional evaluation heuristic. 
+///      board position using `TaperedEvaluator`, material plus middlegame/endgame piece-square tables. 
 ///      It increments the `position_counter` on the `controller` to track the number of evaluated positions.
 ///      Returns the evaluation score and `None` for the move at this point.
 ///
@@ -174,10 +272,12 @@ pub fn min_max_simple(controller: &mut BoardController, depth: i32) -> (i32, Opt
 /// 3. **Recursive Exploration**:
 ///    - Iterates through the sorted move list, performing the following for each move:
 ///      - Makes the move, updates the game state, and recurses into the enemy's perspective.
-///      - Uses the "null-window" approach to perform a preliminary alpha-beta search between `-(alpha + 1)`
-///        and `-alpha`.
+///      - The first move is expected to be the principal variation and is searched with the full
+///        `-betta` to `-alpha` window.
+///      - Every other move gets a preliminary "null-window" search between `-(alpha + 1)` and `-alpha`,
+///        which only proves that the move is not better than the principal one.
 ///      - If the preliminary result suggests a better score might exist (within the range of `alpha` to `betta`),
-///        a deeper full alpha-beta search is performed between `-betta` and `-alpha`.
+///        the move is re-searched with the full window between `-betta` and `-alpha`.
 ///      - After the recursive call, the game state is reverted to preserve the state before the move.
 ///
 /// 4. **Alpha-Beta Updates**:
@@ -194,33 +294,127 @@ pub fn min_max_simple(controller: &mut BoardController, depth: i32) -> (i32, Opt
 ///   (number of valid moves at each position) and `d` is the depth of the search.
 /// - Worst Case: Without pruning, the complexity is closer to `O(b^d)`.
 ///
-pub fn alpha_betta(controller: &mut BoardController, depth: i32, mut alpha: i32, betta: i32) -> (i32, Option<Move>) {
+pub fn alpha_betta(controller: &mut BoardController, depth: i32, alpha: i32, betta: i32) -> (i32, Option<Move>) {
+    alpha_betta_with(&SearchContext::default(), controller, depth, alpha, betta)
+}
+
+/// Shared state of one search: an optional transposition table, an optional stop flag
+/// which makes the search unwind as soon as it is raised, the number of plies a single
+/// line may be extended by (zero disables extensions), the leaf evaluator and optional
+/// endgame tables.
+#[derive(Copy, Clone)]
+pub struct SearchContext<'t> {
+    pub tt: Option<&'t TranspositionTable>,
+    pub stop: Option<&'t AtomicBool>,
+    pub max_extension: i32,
+    pub evaluator: &'t dyn Evaluator,
+    pub tablebase: Option<&'t Tablebase>,
+}
+
+impl<'t> Default for SearchContext<'t> {
+    fn default() -> Self {
+        SearchContext { tt: None, stop: None, max_extension: 0, evaluator: &*DEFAULT_EVALUATOR, tablebase: None }
+    }
+}
+
+impl<'t> SearchContext<'t> {
+    pub fn is_stopped(&self) -> bool {
+        self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+}
+
+/// `alpha_betta` running in a `SearchContext`.
+///
+/// With a transposition table, every node is probed before move generation: a deep enough
+/// entry whose bound proves the score outside of `(alpha, betta)` cuts the node off, and the
+/// stored best move is searched first otherwise. Results are stored back with the bound
+/// they were found with. A stopped search returns immediately and stores nothing, its
+/// result must be discarded by the caller.
+///
+/// While the line has extension budget left (`context.max_extension`), a move giving check is
+/// searched one ply deeper, and so is a position where the side to move is in check and has a
+/// single legal reply. Whether a move gives check is tested once, by the parent, and handed to
+/// the child. Legal replies are only counted in check, among the generated movies and no further
+/// than the second one.
+///
+/// Positions reached by a move are first probed in `context.tablebase`, a covered position is
+/// scored by its table entry instead of being searched.
+pub fn alpha_betta_with(context: &SearchContext, controller: &mut BoardController, depth: i32, alpha: i32, betta: i32) -> (i32, Option<Move>) {
+    let in_check = context.max_extension > 0 && controller.is_in_check();
+    alpha_betta_node(context, controller, depth, alpha, betta, 0, in_check)
+}
+
+/// Whether exactly one of the friend `move_list` movies doesn't leave the friend king under attack.
+fn has_single_legal_move(controller: &mut BoardController, move_list: &MoveList) -> bool {
+    let mut legal = 0;
+    for movement in move_list.iter() {
+        let move_info = controller.make_move(movement);
+        if !controller.is_in_check() { legal += 1 }
+        controller.unmake_move(move_info);
+        if legal > 1 { return false }
+    }
+    legal == 1
+}
+
+/// `in_check` is only known while `extended` is below `context.max_extension`, it is false otherwise.
+fn alpha_betta_node(context: &SearchContext, controller: &mut BoardController, depth: i32, mut alpha: i32, betta: i32, mut extended: i32, in_check: bool) -> (i32, Option<Move>) {
     if depth <= 0 {
         controller.position_counter += 1;
-        return (evaluate_score(controller, |p, f| {
-            material_fn(p, f) + simple_positional_fn(p, f)
-        }), None);
+        return (context.evaluator.evaluate(controller), None);
+    }
+
+    if context.is_stopped() { return (0, None) }
+
+    let hash = context.tt.map(|_| zobrist::hash(controller.board, controller.friend_color));
+    let mut tt_move = None;
+    if let (Some(tt), Some(hash)) = (context.tt, hash) {
+        if let Some(entry) = tt.probe(hash) {
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::EXACT => return (entry.score, entry.best_move),
+                    Bound::LOWER if entry.score >= betta => return (entry.score, entry.best_move),
+                    Bound::UPPER if entry.score <= alpha => return (entry.score, entry.best_move),
+                    _ => {}
+                }
+            }
+            tt_move = entry.best_move;
+        }
     }
 
     let mut move_list = controller.friend_movies();
     move_list.sort_by(controller.board, simple_positional_fn);
+    if let Some(m) = tt_move { move_list.move_to_front(&m) }
 
-    // if let Some(first_move) = move_list.iter().next() {
-    //     let f = self.board.point(first_move.to);
-    //     if f.rank() == KING && f.color() == self.enemy_color && first_move.m_type == SIMPLE {
-    //         return (W_INFINITY, Some(*first_move));
-    //     }
-    // }
+    let mut search_depth = depth;
+    if extended < context.max_extension && in_check && has_single_legal_move(controller, &move_list) {
+        search_depth += 1;
+        extended += 1;
+    }
 
+    let alpha_orig = alpha;
     let mut best_score = - W_INFINITY;
     let mut best_move: Option<Move> = move_list.iter().next().copied();
-    for movement in move_list.iter() {
+    for (i, movement) in move_list.iter().enumerate() {
         let move_info = controller.make_move(movement);
         controller.pass_move_to_enemy();
 
-        let mut cur_score = - alpha_betta(controller, depth - 1, - (alpha + 1), - alpha).0;
-        if cur_score > alpha && cur_score < betta {
-            cur_score = - alpha_betta(controller, depth - 1, - betta, - alpha).0;
+        let gives_check = extended < context.max_extension && controller.is_in_check();
+        let (child_depth, child_extended) = if gives_check {
+            (search_depth, extended + 1)
+        } else {
+            (search_depth - 1, extended)
+        };
+
+        let mut cur_score;
+        if let Some(entry) = context.tablebase.and_then(|tb| tb.probe_controller(controller)) {
+            cur_score = - entry.score();
+        } else if i == 0 {
+            cur_score = - alpha_betta_node(context, controller, child_depth, - betta, - alpha, child_extended, gives_check).0;
+        } else {
+            cur_score = - alpha_betta_node(context, controller, child_depth, - (alpha + 1), - alpha, child_extended, gives_check).0;
+            if cur_score > alpha && cur_score < betta {
+                cur_score = - alpha_betta_node(context, controller, child_depth, - betta, - alpha, child_extended, gives_check).0;
+            }
         }
 
         controller.pass_move_to_enemy();
@@ -232,8 +426,64 @@ pub fn alpha_betta(controller: &mut BoardController, depth: i32, mut alpha: i32,
         }
 
         if best_score > alpha { alpha = best_score }
-        if alpha >= betta { return (alpha, best_move) }
+        if alpha >= betta { break }
+    }
+
+    if context.is_stopped() { return (best_score, best_move) }
+
+    if let (Some(tt), Some(hash)) = (context.tt, hash) {
+        let bound = if best_score <= alpha_orig {
+            Bound::UPPER
+        } else if best_score >= betta {
+            Bound::LOWER
+        } else {
+            Bound::EXACT
+        };
+        tt.store(hash, TtEntry { depth, score: best_score, bound, best_move });
     }
 
     (best_score, best_move)
 }
+
+/// Runs `alpha_betta_with` for depths `1..=max_depth`, each iteration seeding the move ordering
+/// of the next one through the transposition table of `context`. From the second iteration on
+/// the search starts with an aspiration window of `window` around the previous score, see
+/// `aspiration_search`. Returns the result of the last iteration that was not interrupted by
+/// the stop flag.
+pub fn iterative_deepening(context: &SearchContext, controller: &mut BoardController, max_depth: i32, window: i32) -> (i32, Option<Move>) {
+    let mut result = alpha_betta_with(context, controller, 1.min(max_depth), - W_INFINITY, W_INFINITY);
+    for depth in 2..=max_depth {
+        let iteration = aspiration_search(context, controller, depth, result.0, window);
+        if context.is_stopped() { break }
+        result = iteration;
+    }
+    result
+}
+
+/// Searches `depth` plies within `(guess - window, guess + window)`. A score at or below the
+/// lower bound (fail-low) or at or above the upper bound (fail-high) is not exact, so the failed
+/// side of the window is widened, doubling the step each time, and the position is searched again
+/// until the score lands inside the window or the window reaches `W_INFINITY`.
+pub fn aspiration_search(context: &SearchContext, controller: &mut BoardController, depth: i32, guess: i32, window: i32) -> (i32, Option<Move>) {
+    if window <= 0 {
+        return alpha_betta_with(context, controller, depth, - W_INFINITY, W_INFINITY);
+    }
+
+    let mut alpha_delta = window;
+    let mut betta_delta = window;
+    loop {
+        let alpha = (guess - alpha_delta).max(- W_INFINITY);
+        let betta = (guess + betta_delta).min(W_INFINITY);
+
+        let result = alpha_betta_with(context, controller, depth, alpha, betta);
+        if context.is_stopped() { return result }
+
+        if result.0 <= alpha && alpha > - W_INFINITY {
+            alpha_delta *= 2;
+        } else if result.0 >= betta && betta < W_INFINITY {
+            betta_delta *= 2;
+        } else {
+            return result;
+        }
+    }
+}
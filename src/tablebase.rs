@@ -0,0 +1,610 @@
+//! Endgame tablebases built by retrograde analysis.
+//!
+//! A table covers one material signature like `KQK` or `KRKP`, the stronger side first, and
+//! stores every placement of its figures with either side to move. Positions of the weaker side
+//! playing white are probed through the color flipped placement. Values are seen from the side
+//! to move: win, draw or loss, and the distance to mate in plies for decided positions. The rules
+//! are the ones of `MoveGenerator`: no castling, no en passant, promotion to a queen only and
+//! only by a push, a pawn capturing onto the last rank stays there as a pawn. A side with no legal
+//! moves is mated when in check and stalemated otherwise.
+//!
+//! Generation starts from the mates found while counting the legal moves of every placement and
+//! walks back through the moves which lead to decided positions: a position with a move into a
+//! loss is won one ply later, a position whose every move leads into a win is lost once the
+//! longest of them is known. Captures and promotions leave the table, their results are probed
+//! from the tables of the smaller material, which are generated first.
+//!
+//! A placement is indexed by the cells `y * 8 + x` of its figures, white king, white figures in
+//! `Rank` order, black king, black figures, as a number in base 64 doubled plus one when black
+//! is to move. Each table is saved as two files of one byte per index: `<signature>.wdl` holding
+//! `Wdl` values or 3 for impossible placements, and `<signature>.dtm` with the distances.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::{fmt, fs, io, mem};
+
+use crate::board::ByteBoard;
+use crate::board_controller::BoardController;
+use crate::figure::{Color, Figure, Rank, W_INFINITY};
+use crate::figure_list::FigurePointList;
+use crate::movement::{for_each_attack, Move, MoveGenerator, MoveList, MoveType};
+use crate::point::Point;
+
+/// Largest number of figures, kings included, a table may have.
+pub const MAX_MEN: usize = 4;
+/// Score of a won position mated right away, every ply to mate costs one point.
+pub const TB_WIN: i32 = W_INFINITY / 2;
+pub const DEFAULT_TABLEBASE_DIR: &str = "tablebases";
+pub const DEFAULT_SIGNATURES: [&str; 5] = ["KQK", "KRK", "KPK", "KBNK", "KRKP"];
+
+const INVALID: u8 = 3;
+
+#[repr(u8)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Wdl {
+    DRAW,
+    WIN,
+    LOSS,
+}
+
+impl Wdl {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Wdl::DRAW),
+            1 => Some(Wdl::WIN),
+            2 => Some(Wdl::LOSS),
+            _ => None,
+        }
+    }
+}
+
+/// Probe result from the point of view of the side to move.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct TbEntry {
+    pub wdl: Wdl,
+    /// Plies to mate, zero for draws and for mated positions.
+    pub dtm: u8,
+}
+
+impl TbEntry {
+    /// Search score, `TB_WIN` less the plies to mate for wins so that shorter mates are preferred.
+    pub fn score(&self) -> i32 {
+        match self.wdl {
+            Wdl::WIN => TB_WIN - self.dtm as i32,
+            Wdl::LOSS => - (TB_WIN - self.dtm as i32),
+            Wdl::DRAW => 0,
+        }
+    }
+}
+
+fn rank_char(rank: Rank) -> char {
+    match rank {
+        Rank::QUEEN => 'Q',
+        Rank::ROOK => 'R',
+        Rank::BISHOP => 'B',
+        Rank::KNIGHT => 'N',
+        Rank::PAWN => 'P',
+        _ => unreachable!("{:?} has no signature letter", rank),
+    }
+}
+
+fn char_rank(c: char) -> Option<Rank> {
+    match c {
+        'Q' => Some(Rank::QUEEN),
+        'R' => Some(Rank::ROOK),
+        'B' => Some(Rank::BISHOP),
+        'N' => Some(Rank::KNIGHT),
+        'P' => Some(Rank::PAWN),
+        _ => None,
+    }
+}
+
+fn sorted(ranks: &[Rank]) -> Vec<Rank> {
+    let mut ranks = ranks.to_vec();
+    ranks.sort_by_key(|r| *r as u8);
+    ranks
+}
+
+fn side_signature(ranks: &[Rank]) -> String {
+    std::iter::once('K').chain(sorted(ranks).into_iter().map(rank_char)).collect()
+}
+
+fn side_value(ranks: &[Rank]) -> i32 {
+    ranks.iter().map(|r| Figure::new(*r, Color::WHITE, false).weight()).sum()
+}
+
+/// Signature of the material with the stronger side first, and whether it had to swap the sides.
+fn canonical(white: &[Rank], black: &[Rank]) -> (String, bool) {
+    let (w, b) = (side_signature(white), side_signature(black));
+    if (side_value(white), &w) >= (side_value(black), &b) {
+        (w + &b, false)
+    } else {
+        (b + &w, true)
+    }
+}
+
+/// Figures of both sides but the kings, parsed from a signature like `KRKP`.
+fn parse_signature(signature: &str) -> Result<(Vec<Rank>, Vec<Rank>), fmt::Error> {
+    let signature = signature.trim().to_ascii_uppercase();
+    let (white, black) = signature.strip_prefix('K')
+        .and_then(|s| s.split_once('K'))
+        .ok_or(fmt::Error)?;
+
+    let parse = |side: &str| side.chars().map(|c| char_rank(c).ok_or(fmt::Error)).collect::<Result<Vec<_>, _>>();
+    let (white, black) = (parse(white)?, parse(black)?);
+    if white.len() + black.len() + 2 > MAX_MEN { return Err(fmt::Error) }
+    Ok((white, black))
+}
+
+fn table_figures(white: &[Rank], black: &[Rank]) -> Vec<Figure> {
+    let side = |color: Color, ranks: &[Rank]| {
+        std::iter::once(Rank::KING)
+            .chain(sorted(ranks))
+            .map(move |r| Figure::new(r, color, false))
+            .collect::<Vec<_>>()
+    };
+    let mut figures = side(Color::WHITE, white);
+    figures.extend(side(Color::BLACK, black));
+    figures
+}
+
+fn table_size(men: usize) -> usize {
+    2 << (6 * men)
+}
+
+fn cell(p: Point) -> usize {
+    p.y() as usize * 8 + p.x() as usize
+}
+
+fn encode(color: Color, squares: &[Point]) -> usize {
+    let placement = squares.iter().fold(0, |index, p| index * 64 + cell(*p));
+    placement * 2 + (color == Color::BLACK) as usize
+}
+
+fn decode(index: usize, men: usize) -> (Color, [Point; MAX_MEN]) {
+    let color = if index & 1 == 1 { Color::BLACK } else { Color::WHITE };
+    let mut placement = index >> 1;
+    let mut squares = [Point::default(); MAX_MEN];
+    for square in squares[..men].iter_mut().rev() {
+        *square = Point::new((placement % 8) as i8, (placement / 8 % 8) as i8);
+        placement /= 64;
+    }
+    (color, squares)
+}
+
+/// Plays `movement` on `board` the way `BoardController::make_move` does.
+fn apply_move(board: &mut ByteBoard, movement: &Move) {
+    match movement.m_type {
+        MoveType::SIMPLE => {
+            *board.point_mut(movement.to) = *board.point(movement.from);
+            *board.point_mut(movement.from) = Figure::empty();
+        }
+        MoveType::SWAP => board.swap(movement.from, movement.to),
+        MoveType::TRANSFORM => {
+            let color = board.point(movement.from).color();
+            *board.point_mut(movement.from) = Figure::empty();
+            *board.point_mut(movement.to) = Figure::new(movement.promoted_rank(), color, false);
+        }
+    }
+}
+
+fn is_figure(figure: Figure) -> bool {
+    figure.rank() != Rank::NONE && figure.rank() != Rank::OUT
+}
+
+/// Win, draw or loss and distance to mate of every placement of one material signature.
+pub struct Table {
+    figures: Vec<Figure>,
+    wdl: Vec<u8>,
+    dtm: Vec<u8>,
+}
+
+impl Table {
+    pub fn len(&self) -> usize {
+        self.wdl.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wdl.is_empty()
+    }
+
+    /// Number of possible positions with the side to move getting `wdl`.
+    pub fn count(&self, wdl: Wdl) -> usize {
+        self.wdl.iter().filter(|v| **v == wdl as u8).count()
+    }
+
+    /// Longest distance to mate in plies over all positions.
+    pub fn longest_mate(&self) -> u8 {
+        self.wdl.iter()
+            .zip(self.dtm.iter())
+            .filter(|(wdl, _)| **wdl != Wdl::DRAW as u8 && **wdl != INVALID)
+            .map(|(_, dtm)| *dtm)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Entry of the figures, which must match the table's material.
+    fn probe(&self, figures: &[(Point, Figure)], color: Color) -> Option<TbEntry> {
+        let mut squares = [Point::default(); MAX_MEN];
+        let mut used = [false; MAX_MEN];
+        for (p, f) in figures {
+            let slot = (0..self.figures.len())
+                .find(|i| !used[*i] && self.figures[*i].rank() == f.rank() && self.figures[*i].color() == f.color())?;
+            used[slot] = true;
+            squares[slot] = *p;
+        }
+
+        let index = encode(color, &squares[..self.figures.len()]);
+        Wdl::from_u8(self.wdl[index]).map(|wdl| TbEntry { wdl, dtm: self.dtm[index] })
+    }
+}
+
+/// Set of tables probed by the searches, see the module docs.
+#[derive(Default)]
+pub struct Tablebase {
+    tables: HashMap<String, Table>,
+}
+
+impl Tablebase {
+    pub fn new() -> Self {
+        Tablebase::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Signatures of the tables, sorted.
+    pub fn signatures(&self) -> Vec<&str> {
+        let mut signatures: Vec<&str> = self.tables.keys().map(String::as_str).collect();
+        signatures.sort();
+        signatures
+    }
+
+    pub fn table(&self, signature: &str) -> Option<&Table> {
+        let (white, black) = parse_signature(signature).ok()?;
+        self.tables.get(&canonical(&white, &black).0)
+    }
+
+    /// Generates the table of `signature` and the tables of every material it can turn into,
+    /// skipping the ones already present. Fails for malformed signatures and for more than
+    /// `MAX_MEN` figures.
+    pub fn generate(&mut self, signature: &str) -> Result<(), fmt::Error> {
+        let (white, black) = parse_signature(signature)?;
+        let (signature, flipped) = canonical(&white, &black);
+        if self.tables.contains_key(&signature) || white.len() + black.len() == 0 { return Ok(()) }
+        let (white, black) = if flipped { (black, white) } else { (white, black) };
+
+        let with_side = |is_white: bool, side: &[Rank]| if is_white {
+            side_signature(side) + &side_signature(&black)
+        } else {
+            side_signature(&white) + &side_signature(side)
+        };
+        let mut children = Vec::new();
+        for (is_white, side) in [(true, &white), (false, &black)] {
+            for i in 0..side.len() {
+                let mut rest = side.to_vec();
+                let removed = rest.remove(i);
+                children.push(with_side(is_white, &rest));
+                if removed == Rank::PAWN {
+                    rest.push(Rank::QUEEN);
+                    children.push(with_side(is_white, &rest));
+                }
+            }
+        }
+        for child in children.iter() {
+            self.generate(child)?;
+        }
+
+        let table = TableGenerator::new(self, table_figures(&white, &black)).run();
+        self.tables.insert(signature, table);
+        Ok(())
+    }
+
+    /// Entry of `board` with `color` to move, `None` when no table covers the material.
+    /// Bare kings are always a draw.
+    pub fn probe(&self, board: &ByteBoard, color: Color) -> Option<TbEntry> {
+        let figures: Vec<(Point, Figure)> = board.cell_iter()
+            .filter(|(_, f)| is_figure(**f))
+            .map(|(p, f)| (p, *f))
+            .collect();
+        self.probe_figures(figures, color)
+    }
+
+    /// `probe` of the controller position with the friend side to move, cheap to call for
+    /// positions with more than `MAX_MEN` figures.
+    pub fn probe_controller(&self, controller: &BoardController) -> Option<TbEntry> {
+        if controller.friend_list().iter().count() + controller.enemy_list().iter().count() > MAX_MEN { return None }
+        let figures: Vec<(Point, Figure)> = controller.friend_list().iter()
+            .chain(controller.enemy_list().iter())
+            .map(|p| (p, *controller.board().point(p)))
+            .collect();
+        self.probe_figures(figures, controller.friend_color())
+    }
+
+    fn probe_figures(&self, figures: Vec<(Point, Figure)>, color: Color) -> Option<TbEntry> {
+        if figures.len() > MAX_MEN { return None }
+        let ranks = |side: Color| figures.iter()
+            .filter(|(_, f)| f.color() == side && f.rank() != Rank::KING)
+            .map(|(_, f)| f.rank())
+            .collect::<Vec<_>>();
+        let kings = |side: Color| figures.iter().filter(|(_, f)| f.color() == side && f.rank() == Rank::KING).count();
+        if kings(Color::WHITE) != 1 || kings(Color::BLACK) != 1 { return None }
+
+        let (signature, flipped) = canonical(&ranks(Color::WHITE), &ranks(Color::BLACK));
+        if figures.len() == 2 { return Some(TbEntry { wdl: Wdl::DRAW, dtm: 0 }) }
+
+        let table = self.tables.get(&signature)?;
+        if flipped {
+            let figures: Vec<(Point, Figure)> = figures.iter()
+                .map(|(p, f)| (Point::new(p.x(), 7 - p.y()), Figure::new(f.rank(), f.color().invert(), false)))
+                .collect();
+            table.probe(&figures, color.invert())
+        } else {
+            table.probe(&figures, color)
+        }
+    }
+
+    /// Best legal move of the friend side by the tables with its score: the fastest mate,
+    /// a move keeping the draw or the longest resistance. `None` when the position is not covered.
+    pub fn best_move(&self, controller: &mut BoardController) -> Option<(i32, Move)> {
+        self.probe_controller(controller)?;
+        let color = controller.friend_color();
+        controller.legal_movies().iter()
+            .filter_map(|m| {
+                let mut board = *controller.board();
+                apply_move(&mut board, m);
+                self.probe(&board, color.invert()).map(|entry| (- entry.score(), *m))
+            })
+            .max_by_key(|(score, _)| *score)
+    }
+
+    /// Loads every `<signature>.wdl` table of `path` with its `.dtm` file.
+    pub fn load_dir<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut tablebase = Tablebase::new();
+        for entry in fs::read_dir(&path)? {
+            let file = entry?.path();
+            if file.extension().is_none_or(|e| e != "wdl") { continue }
+
+            let name = file.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            let (white, black) = parse_signature(&name).map_err(|_| invalid(format!("Unknown table {}", file.display())))?;
+            let (signature, flipped) = canonical(&white, &black);
+            if signature != name || flipped {
+                return Err(invalid(format!("Table {} should be named {}", name, signature)));
+            }
+
+            let figures = table_figures(&white, &black);
+            let wdl = fs::read(&file)?;
+            let dtm = fs::read(file.with_extension("dtm"))?;
+            if wdl.len() != table_size(figures.len()) || dtm.len() != wdl.len() {
+                return Err(invalid(format!("Table {} has a wrong size", name)));
+            }
+            tablebase.tables.insert(signature, Table { figures, wdl, dtm });
+        }
+        Ok(tablebase)
+    }
+
+    /// Writes every table to `path`, creating the directory if needed.
+    pub fn save_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::create_dir_all(&path)?;
+        for (signature, table) in self.tables.iter() {
+            fs::write(path.as_ref().join(format!("{}.wdl", signature)), &table.wdl)?;
+            fs::write(path.as_ref().join(format!("{}.dtm", signature)), &table.dtm)?;
+        }
+        Ok(())
+    }
+}
+
+struct TableGenerator<'a> {
+    tablebase: &'a Tablebase,
+    figures: Vec<Figure>,
+    empty_board: ByteBoard,
+    /// `MoveGenerator` wants a list, moves are generated figure by figure so it stays empty.
+    empty_list: FigurePointList,
+}
+
+impl<'a> TableGenerator<'a> {
+    fn new(tablebase: &'a Tablebase, figures: Vec<Figure>) -> Self {
+        let empty_board = ByteBoard::empty();
+        TableGenerator { tablebase, figures, empty_board, empty_list: FigurePointList::new(&empty_board, Color::WHITE) }
+    }
+
+    fn men(&self) -> usize {
+        self.figures.len()
+    }
+
+    fn board(&self, squares: &[Point]) -> ByteBoard {
+        let mut board = self.empty_board;
+        for (p, f) in squares.iter().zip(self.figures.iter()) {
+            *board.point_mut(*p) = *f;
+        }
+        board
+    }
+
+    fn king(&self, color: Color, squares: &[Point]) -> Point {
+        let slot = (0..self.men()).find(|i| self.figures[*i].rank() == Rank::KING && self.figures[*i].color() == color).unwrap();
+        squares[slot]
+    }
+
+    /// Whether a figure of `by` attacks `target`, the figure in slot `captured` is not counted.
+    fn is_attacked(&self, board: &ByteBoard, squares: &[Point], captured: Option<usize>, target: Point, by: Color) -> bool {
+        let mut attacked = false;
+        for i in (0..self.men()).filter(|i| self.figures[*i].color() == by && Some(*i) != captured) {
+            for_each_attack(board, squares[i], |p| attacked |= p == target);
+            if attacked { return true }
+        }
+        false
+    }
+
+    /// Figures on distinct cells, no pawn on its own first rank and the side which has just
+    /// moved not in check.
+    fn is_valid(&self, color: Color, squares: &[Point], board: &ByteBoard) -> bool {
+        let mut cells = 0u64;
+        for (p, f) in squares.iter().zip(self.figures.iter()) {
+            if cells & (1 << cell(*p)) != 0 { return false }
+            cells |= 1 << cell(*p);
+            if f.rank() == Rank::PAWN && p.y() == if f.color() == Color::WHITE { 0 } else { 7 } { return false }
+        }
+        !self.is_attacked(board, squares, None, self.king(color.invert(), squares), color)
+    }
+
+    /// Calls `f` for every legal move of `color` with `None` for moves staying in the table
+    /// and the entry of the position reached otherwise, by a capture or a promotion.
+    fn for_each_successor<F: FnMut(Option<TbEntry>)>(&self, color: Color, squares: &[Point], board: &ByteBoard, mut f: F) {
+        let generator = MoveGenerator::new(board, &self.empty_list);
+        let mut move_list = MoveList::default();
+        for slot in (0..self.men()).filter(|i| self.figures[*i].color() == color) {
+            move_list.clear();
+            generator.fill_for_figure(squares[slot], &mut move_list);
+            for movement in move_list.iter() {
+                let mut child_board = *board;
+                apply_move(&mut child_board, movement);
+                let mut child = [Point::default(); MAX_MEN];
+                child[..self.men()].copy_from_slice(squares);
+                child[slot] = movement.to;
+
+                let captured = (0..self.men()).find(|i| *i != slot && squares[*i] == movement.to);
+                let king = self.king(color, &child[..self.men()]);
+                if self.is_attacked(&child_board, &child[..self.men()], captured, king, color.invert()) { continue }
+
+                if captured.is_none() && movement.m_type != MoveType::TRANSFORM {
+                    f(None);
+                } else {
+                    let entry = self.tablebase.probe(&child_board, color.invert())
+                        .expect("Tables of smaller material should be generated first");
+                    f(Some(entry));
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the index of every placement in the table from which the side which has
+    /// just moved could have reached `squares` without capturing or promoting.
+    fn for_each_predecessor<F: FnMut(usize)>(&self, color: Color, squares: &[Point], board: &ByteBoard, mut f: F) {
+        let mover = color.invert();
+        let generator = MoveGenerator::new(board, &self.empty_list);
+        let mut move_list = MoveList::default();
+        let mut emit = |slot: usize, from: Point| {
+            let mut parent = [Point::default(); MAX_MEN];
+            parent[..self.men()].copy_from_slice(squares);
+            parent[slot] = from;
+            f(encode(mover, &parent[..self.men()]));
+        };
+
+        for slot in (0..self.men()).filter(|i| self.figures[*i].color() == mover) {
+            let to = squares[slot];
+            if self.figures[slot].rank() == Rank::PAWN {
+                let (dy, first, last) = if mover == Color::WHITE { (1, 0, 7) } else { (-1, 7, 0) };
+                let one = Point::new(to.x(), to.y() - dy);
+                if to.y() != last && one.y() != first && board.point(one).rank() == Rank::NONE {
+                    emit(slot, one);
+                    let two = Point::new(to.x(), to.y() - 2 * dy);
+                    if two.y() == first + dy && board.point(two).rank() == Rank::NONE { emit(slot, two) }
+                }
+            } else {
+                move_list.clear();
+                generator.fill_for_figure(to, &mut move_list);
+                move_list.iter()
+                    .filter(|m| board.point(m.to).rank() == Rank::NONE)
+                    .for_each(|m| emit(slot, m.to));
+            }
+        }
+    }
+
+    fn run(&self) -> Table {
+        let size = table_size(self.men());
+        let mut wdl = vec![Wdl::DRAW as u8; size];
+        let mut dtm = vec![0u8; size];
+        let mut moves_left = vec![0u8; size];
+        let mut longest = vec![0u8; size];
+        let mut escapes = vec![false; size];
+        let mut resolved = vec![false; size];
+        let mut levels: Vec<Vec<u32>> = Vec::new();
+        let schedule = |levels: &mut Vec<Vec<u32>>, index: usize, level: usize| {
+            if levels.len() <= level { levels.resize_with(level + 1, Vec::new) }
+            levels[level].push(index as u32);
+        };
+
+        for index in 0..size {
+            let (color, squares) = decode(index, self.men());
+            let squares = &squares[..self.men()];
+            let board = self.board(squares);
+            if !self.is_valid(color, squares, &board) {
+                wdl[index] = INVALID;
+                resolved[index] = true;
+                continue;
+            }
+
+            let (mut moves, mut best_win) = (0, None);
+            self.for_each_successor(color, squares, &board, |successor| {
+                moves += 1;
+                match successor {
+                    None => moves_left[index] += 1,
+                    Some(entry) => match entry.wdl {
+                        Wdl::LOSS => best_win = Some(best_win.unwrap_or(u8::MAX).min(entry.dtm + 1)),
+                        Wdl::DRAW => escapes[index] = true,
+                        Wdl::WIN => longest[index] = longest[index].max(entry.dtm + 1),
+                    },
+                }
+            });
+
+            if moves == 0 {
+                if self.is_attacked(&board, squares, None, self.king(color, squares), color.invert()) {
+                    wdl[index] = Wdl::LOSS as u8;
+                    schedule(&mut levels, index, 0);
+                } else {
+                    resolved[index] = true;
+                }
+            } else if let Some(win) = best_win {
+                wdl[index] = Wdl::WIN as u8;
+                dtm[index] = win;
+                schedule(&mut levels, index, win as usize);
+            } else if moves_left[index] == 0 {
+                if escapes[index] {
+                    resolved[index] = true;
+                } else {
+                    wdl[index] = Wdl::LOSS as u8;
+                    dtm[index] = longest[index];
+                    schedule(&mut levels, index, longest[index] as usize);
+                }
+            }
+        }
+
+        let mut level = 0;
+        while level < levels.len() {
+            let next = u8::try_from(level + 1).expect("Distance to mate does not fit the table");
+            for index in mem::take(&mut levels[level]) {
+                let index = index as usize;
+                if resolved[index] { continue }
+                resolved[index] = true;
+
+                let (color, squares) = decode(index, self.men());
+                let squares = &squares[..self.men()];
+                let won = wdl[index] == Wdl::WIN as u8;
+                self.for_each_predecessor(color, squares, &self.board(squares), |parent| {
+                    if resolved[parent] { return }
+                    if !won {
+                        if wdl[parent] != Wdl::WIN as u8 || dtm[parent] > next {
+                            wdl[parent] = Wdl::WIN as u8;
+                            dtm[parent] = next;
+                            schedule(&mut levels, parent, next as usize);
+                        }
+                    } else {
+                        moves_left[parent] -= 1;
+                        longest[parent] = longest[parent].max(next);
+                        if moves_left[parent] == 0 && wdl[parent] != Wdl::WIN as u8 && !escapes[parent] {
+                            wdl[parent] = Wdl::LOSS as u8;
+                            dtm[parent] = longest[parent];
+                            schedule(&mut levels, parent, longest[parent] as usize);
+                        }
+                    }
+                });
+            }
+            level += 1;
+        }
+
+        Table { figures: self.figures.clone(), wdl, dtm }
+    }
+}
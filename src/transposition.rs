@@ -0,0 +1,107 @@
+//! Lock-free transposition table shared between search threads.
+//!
+//! Each slot holds two atomics: the packed entry data and the position hash XOR-ed with it.
+//! A reader only accepts a slot when `key ^ data` gives back the probed hash, so an entry
+//! torn by a concurrent writer is rejected instead of being misread.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::movement::Move;
+
+pub const DEFAULT_TT_SIZE_MB: usize = 16;
+
+#[repr(u8)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Bound {
+    EXACT,
+    LOWER,
+    UPPER,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct TtEntry {
+    pub depth: i32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+#[derive(Default)]
+struct TtSlot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+pub struct TranspositionTable {
+    slots: Vec<TtSlot>,
+}
+
+/// Layout: score in bits 0..32, depth in 32..40, bound in 40..42, packed move in 42..58.
+fn pack(entry: &TtEntry) -> u64 {
+    (entry.score as u32 as u64)
+        | ((entry.depth.clamp(0, 255) as u64) << 32)
+        | ((entry.bound as u64) << 40)
+        | ((entry.best_move.map_or(0, |m| m.pack()) as u64) << 42)
+}
+
+fn unpack(data: u64) -> TtEntry {
+    TtEntry {
+        score: data as u32 as i32,
+        depth: ((data >> 32) & 0xFF) as i32,
+        bound: match (data >> 40) & 3 {
+            0 => Bound::EXACT,
+            1 => Bound::LOWER,
+            _ => Bound::UPPER,
+        },
+        best_move: Move::unpack((data >> 42) as u16),
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a table taking about `size_mb` megabytes, rounded down to a power of two slots.
+    pub fn new(size_mb: usize) -> Self {
+        let fit = size_mb.max(1) * 1024 * 1024 / std::mem::size_of::<TtSlot>();
+        let slot_count = 1usize << (usize::BITS - 1 - fit.leading_zeros());
+        let mut slots = Vec::with_capacity(slot_count);
+        slots.resize_with(slot_count, TtSlot::default);
+        TranspositionTable { slots }
+    }
+
+    fn slot(&self, hash: u64) -> &TtSlot {
+        &self.slots[hash as usize & (self.slots.len() - 1)]
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<TtEntry> {
+        let slot = self.slot(hash);
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed);
+        if data == 0 || key ^ data != hash { return None }
+        Some(unpack(data))
+    }
+
+    /// Stores `entry` unless the slot already keeps a deeper result for the same position.
+    pub fn store(&self, hash: u64, entry: TtEntry) {
+        let slot = self.slot(hash);
+        if let Some(old) = self.probe(hash) {
+            if old.depth > entry.depth { return }
+        }
+        let data = pack(&entry);
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        self.slots.iter().for_each(|slot| {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
@@ -0,0 +1,196 @@
+//! Texel tuning of `EvalParams`.
+//!
+//! Positions are labelled with the result of the game they were taken from: 1 for a white win,
+//! 0.5 for a draw and 0 for a black win. The tuner minimises the mean squared difference between
+//! the labels and the winning probability predicted from the static evaluation,
+//! `1 / (1 + 10^(-k * centipawns / 400))`, with a local search: every value is moved one step up
+//! or down as long as that lowers the error. Positions should be quiet as there is no quiescence
+//! search to resolve captures.
+
+use std::{fmt, fs, io};
+
+use crate::board::ByteBoard;
+use crate::board_controller::BoardDataHolder;
+use crate::database::DataBaseInstance;
+use crate::eval_params::EvalParams;
+use crate::evaluation::TaperedEvaluator;
+use crate::fen;
+use crate::figure::{Color, Rank};
+use crate::pst::PST_SCALE;
+use crate::score::Evaluator;
+
+pub struct LabeledPosition {
+    pub board: ByteBoard,
+    pub color: Color,
+    /// Game result from the white point of view.
+    pub result: f64,
+}
+
+fn parse_result(result: &str) -> Result<f64, fmt::Error> {
+    match result.trim().trim_matches('"') {
+        "1-0" => Ok(1.0),
+        "0-1" => Ok(0.0),
+        "1/2-1/2" => Ok(0.5),
+        number => number.parse::<f64>().ok().filter(|r| (0.0..=1.0).contains(r)).ok_or(fmt::Error),
+    }
+}
+
+/// Parses a `<fen>; <result>` or `<fen> [<result>]` line, the result being
+/// `1-0`, `0-1`, `1/2-1/2` or a number from 0 to 1.
+pub fn parse_position(line: &str) -> Result<LabeledPosition, fmt::Error> {
+    let (fen, result) = match line.split_once('[') {
+        Some((fen, result)) => (fen, result.trim_end().strip_suffix(']').ok_or(fmt::Error)?),
+        None => line.split_once(';').ok_or(fmt::Error)?,
+    };
+    let (board, color) = fen::parse(fen)?;
+    Ok(LabeledPosition { board, color, result: parse_result(result)? })
+}
+
+/// Reads one position per line, empty lines and lines starting with `#` are skipped.
+pub fn load_positions(path: &str) -> io::Result<Vec<LabeledPosition>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| parse_position(line)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: malformed position", path, i + 1))))
+        .collect()
+}
+
+/// Positions of every finished game in the database. A game is finished when a king was captured,
+/// the side which captured it won. The capturing position itself is not included.
+pub fn positions_from_database(db: &DataBaseInstance) -> rusqlite::Result<Vec<LabeledPosition>> {
+    let mut positions = Vec::new();
+    for game in db.find_games()? {
+        let mut records = db.find_moves(&game)?;
+        records.sort_by_key(|r| r.move_number);
+
+        let mut holder = BoardDataHolder::new(&ByteBoard::default());
+        let mut game_positions = Vec::new();
+        let mut winner = None;
+        for (i, record) in records.iter().enumerate() {
+            let color = if i % 2 == 0 { Color::WHITE } else { Color::BLACK };
+            let recorded = match record.to_move() {
+                Ok(m) => m,
+                Err(_) => break,
+            };
+
+            let mut controller = holder.controller(color);
+            let movement = match controller.point_movies(recorded.from).iter().find(|m| m.to == recorded.to) {
+                Some(m) => *m,
+                None => break,
+            };
+            let target = *controller.board().point(movement.to);
+            controller.make_move(&movement);
+
+            if target.rank() == Rank::KING {
+                winner = Some(color);
+                break;
+            }
+            game_positions.push(LabeledPosition { board: holder.board, color: color.invert(), result: 0.0 });
+        }
+
+        if let Some(winner) = winner {
+            let result = if winner == Color::WHITE { 1.0 } else { 0.0 };
+            positions.extend(game_positions.into_iter().map(|p| LabeledPosition { result, ..p }));
+        }
+    }
+    Ok(positions)
+}
+
+pub struct TexelTuner {
+    positions: Vec<(BoardDataHolder, f64)>,
+    /// Reused for every evaluated parameter set, see `TaperedEvaluator::set_params`.
+    evaluator: TaperedEvaluator,
+    /// Scale of the evaluation sigmoid, see `fit_k`.
+    pub k: f64,
+}
+
+impl TexelTuner {
+    pub fn new(positions: &[LabeledPosition]) -> Self {
+        TexelTuner {
+            positions: positions.iter().map(|p| (BoardDataHolder::new(&p.board), p.result)).collect(),
+            evaluator: TaperedEvaluator::default(),
+            k: 1.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// White point of view evaluations in centipawns.
+    fn scores(&mut self, params: &EvalParams) -> Vec<f64> {
+        self.evaluator.set_params(params.clone());
+        let evaluator = &self.evaluator;
+        self.positions.iter_mut()
+            .map(|(holder, _)| evaluator.evaluate(&holder.controller(Color::WHITE)) as f64 / PST_SCALE as f64)
+            .collect()
+    }
+
+    fn scores_error(&self, scores: &[f64], k: f64) -> f64 {
+        let sum: f64 = scores.iter()
+            .zip(self.positions.iter())
+            .map(|(score, (_, result))| {
+                let predicted = 1.0 / (1.0 + 10f64.powf(- k * score / 400.0));
+                (result - predicted).powi(2)
+            })
+            .sum();
+        sum / self.positions.len().max(1) as f64
+    }
+
+    /// Mean squared error of the positions evaluated with `params`.
+    pub fn error(&mut self, params: &EvalParams) -> f64 {
+        let scores = self.scores(params);
+        self.scores_error(&scores, self.k)
+    }
+
+    /// Picks the sigmoid scale `k` fitting `params` best. It is meant to be fitted once
+    /// for the starting parameters and kept while tuning.
+    pub fn fit_k(&mut self, params: &EvalParams) -> f64 {
+        let scores = self.scores(params);
+        let mut step = 0.5;
+        for _ in 0..4 {
+            let center = self.k;
+            self.k = (-10..=10)
+                .map(|i| center + i as f64 * step / 10.0)
+                .filter(|k| *k > 0.0)
+                .min_by(|a, b| self.scores_error(&scores, *a).partial_cmp(&self.scores_error(&scores, *b)).unwrap())
+                .unwrap_or(center);
+            step /= 10.0;
+        }
+        self.k
+    }
+
+    /// Runs at most `passes` passes of the local search over all values of `params`,
+    /// stopping early when a pass changes nothing.
+    pub fn tune(&mut self, params: &EvalParams, passes: usize) -> EvalParams {
+        let mut params = params.clone();
+        let mut values = params.values();
+        let mut best = self.error(&params);
+
+        for _ in 0..passes {
+            let mut improved = false;
+            for i in 0..values.len() {
+                for step in [1, -1] {
+                    values[i] += step;
+                    params.set_values(&values);
+                    let error = self.error(&params);
+                    if error < best {
+                        best = error;
+                        improved = true;
+                        break;
+                    }
+                    values[i] -= step;
+                }
+            }
+            params.set_values(&values);
+            if !improved { break }
+        }
+        params
+    }
+}
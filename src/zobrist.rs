@@ -0,0 +1,72 @@
+//! Zobrist hashing of `Board` positions.
+//!
+//! Every (piece, square) pair of the main 8x8 area gets a pseudo-random 64-bit key,
+//! the position hash is the XOR of the keys of all figures on the board plus
+//! `SIDE_KEY` when black is to move. Keys are generated at compile time from a fixed
+//! seed, so hashes are stable between runs and threads.
+
+use crate::board::Board;
+use crate::figure::{Color, Figure, Rank};
+use crate::point::Point;
+
+const fn xorshift(mut state: u64) -> u64 {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state
+}
+
+const fn generate_piece_keys() -> [[u64; 64]; 12] {
+    let mut keys = [[0u64; 64]; 12];
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            state = xorshift(state);
+            keys[piece][square] = state;
+            square += 1;
+        }
+        piece += 1;
+    }
+    keys
+}
+
+static PIECE_KEYS: [[u64; 64]; 12] = generate_piece_keys();
+
+pub const SIDE_KEY: u64 = 0xF3A9_1B2C_5D7E_8049;
+
+fn piece_index(figure: Figure) -> Option<usize> {
+    let rank = match figure.rank() {
+        Rank::NONE | Rank::OUT => return None,
+        rank => rank as usize - 1,
+    };
+    match figure.color() {
+        Color::WHITE => Some(rank),
+        Color::BLACK => Some(rank + 6),
+        _ => None,
+    }
+}
+
+/// Key of `figure` standing on `point`, zero for empty cells.
+pub fn piece_key(point: Point, figure: Figure) -> u64 {
+    match piece_index(figure) {
+        None => 0,
+        Some(i) => PIECE_KEYS[i][(point.x() * 8 + point.y()) as usize],
+    }
+}
+
+/// Full hash of `board` with `color` to move.
+pub fn hash<B: Board>(board: &B, color: Color) -> u64 {
+    let pieces = board.cell_iter()
+        .fold(0, |hash, (p, f)| hash ^ piece_key(p, *f));
+
+    if color == Color::BLACK { pieces ^ SIDE_KEY } else { pieces }
+}
+
+/// Hash of the pawns on `board` only, the key of pawn structure caches.
+pub fn pawn_hash<B: Board>(board: &B) -> u64 {
+    board.cell_iter()
+        .filter(|(_, f)| f.rank() == Rank::PAWN)
+        .fold(0, |hash, (p, f)| hash ^ piece_key(p, *f))
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use chess_algorithm::bitboard::{square, BitBoard};
+use chess_algorithm::board::{Board, ByteBoard};
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::evaluation::TaperedEvaluator;
+use chess_algorithm::fen;
+use chess_algorithm::figure::{Color, Rank};
+use chess_algorithm::movement::{for_each_attack, walk_attacks};
+use chess_algorithm::perft::perft;
+use chess_algorithm::point::Point;
+use chess_algorithm::score::Evaluator;
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w - - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "4k3/1P6/8/3q4/8/2B5/6p1/4K3 b - - 0 1",
+];
+
+fn attacks<B: Board>(board: &B, p: Point, walk: bool) -> HashSet<Point> {
+    let mut attacks = HashSet::new();
+    if walk {
+        walk_attacks(board, p, |to| { attacks.insert(to); });
+    } else {
+        for_each_attack(board, p, |to| { attacks.insert(to); });
+    }
+    attacks
+}
+
+#[test]
+fn test_conversion() {
+    assert_eq!(BitBoard::default().to_board::<ByteBoard>(), ByteBoard::default());
+    assert_eq!(ByteBoard::default().to_board::<BitBoard>(), BitBoard::default());
+    assert_eq!(BitBoard::default().to_string(), ByteBoard::default().to_string());
+
+    let mut board = BitBoard::default();
+    assert_eq!(board.point(Point::new(-1, 0)).rank(), Rank::OUT);
+    assert_eq!(board.figure_mask(Rank::PAWN, Color::WHITE), 0xFF00);
+    assert_eq!(board.occupied(), 0xFFFF_0000_0000_FFFF);
+
+    board.swap(Point::new(3, 1), Point::new(3, 3));
+    assert_eq!(board.figure_mask(Rank::PAWN, Color::WHITE), 0x0800_F700);
+    assert_eq!(board.color_mask(Color::WHITE).count_ones(), 16);
+}
+
+#[test]
+fn test_attacks_match_walking() {
+    for fen in POSITIONS {
+        let (board, _) = fen::parse(fen).unwrap();
+        let bit_board: BitBoard = board.to_board();
+        for (p, f) in board.cell_iter().filter(|(_, f)| f.rank() != Rank::NONE) {
+            let expected = attacks(&board, p, true);
+            assert_eq!(attacks(&bit_board, p, false), expected, "{} {} at {}", fen, f, p);
+            assert_eq!(bit_board.attacks(p).count_ones() as usize, expected.len());
+            assert!(expected.iter().all(|to| bit_board.attacks(p) & 1 << square(*to) != 0));
+        }
+    }
+}
+
+#[test]
+fn test_attackers_match_attacks() {
+    for fen in POSITIONS {
+        let (board, _) = fen::parse(fen).unwrap();
+        let bit_board: BitBoard = board.to_board();
+        for (target, _) in board.cell_iter() {
+            for color in [Color::WHITE, Color::BLACK] {
+                // Figures whose attacks reach the target, read the forward way.
+                let expected = board.cell_iter()
+                    .filter(|(p, f)| f.color() == color && attacks(&board, *p, true).contains(&target))
+                    .fold(0u64, |mask, (p, _)| mask | 1 << square(p));
+                assert_eq!(board.attackers(target, color), expected, "{} {:?} on {}", fen, color, target);
+                assert_eq!(bit_board.attackers(target, color), expected, "{} {:?} on {}", fen, color, target);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_perft_matches() {
+    let mut holder = BoardDataHolder::new(&BitBoard::default());
+    assert_eq!(perft(&mut holder.controller(Color::WHITE), 3), 8902);
+
+    for fen in POSITIONS {
+        let (board, color) = fen::parse(fen).unwrap();
+        let mut byte_holder = BoardDataHolder::new(&board);
+        let mut bit_holder = BoardDataHolder::new(&board.to_board::<BitBoard>());
+        assert_eq!(perft(&mut bit_holder.controller(color), 2), perft(&mut byte_holder.controller(color), 2), "{}", fen);
+        assert_eq!(bit_holder.board.to_board::<ByteBoard>(), board);
+    }
+}
+
+#[test]
+fn test_evaluation_matches() {
+    let evaluator = TaperedEvaluator::default();
+    for fen in POSITIONS {
+        let (board, color) = fen::parse(fen).unwrap();
+        let mut byte_holder = BoardDataHolder::new(&board);
+        let mut bit_holder = BoardDataHolder::new(&board.to_board::<BitBoard>());
+        assert_eq!(evaluator.evaluate(&bit_holder.controller(color)), evaluator.evaluate(&byte_holder.controller(color)), "{}", fen);
+    }
+}
@@ -2,9 +2,9 @@ extern crate chess_algorithm;
 
 use chess_algorithm::board::ByteBoard;
 use chess_algorithm::board_controller::BoardDataHolder;
-use chess_algorithm::figure::Color::WHITE;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
 use chess_algorithm::figure::Figure;
-use chess_algorithm::figure::Rank::PAWN;
+use chess_algorithm::figure::Rank::{PAWN, NONE};
 use chess_algorithm::movement::{Move, MoveType};
 use chess_algorithm::point::Point;
 
@@ -13,11 +13,7 @@ fn transform_test() {
     let mut board = ByteBoard::empty();
     *board.cell_mut(1, 6) = Figure::new(PAWN, WHITE, false);
 
-    let movement = Move {
-        from: Point::new(1, 6),
-        to: Point::new(1, 7),
-        m_type: MoveType::TRANSFORM
-    };
+    let movement = Move::new(Point::new(1, 6), Point::new(1, 7), MoveType::TRANSFORM);
 
     let mut holder =  BoardDataHolder::new(&board);
 
@@ -29,4 +25,60 @@ fn transform_test() {
 
     assert_eq!(board, holder.board);
     assert_eq!(holder.white_list.iter().last().unwrap(), Point::new(1, 6));
-}
\ No newline at end of file
+}
+
+fn play(holder: &mut BoardDataHolder, movies: &[&str]) {
+    for movement in movies {
+        holder.play(&Move::from_string(movement).unwrap());
+    }
+}
+
+#[test]
+fn history_test() {
+    let start = ByteBoard::default();
+    let mut holder = BoardDataHolder::new(&start);
+    play(&mut holder, &["E2E4", "D7D5", "E4D5", "G8F6"]);
+
+    assert_eq!(holder.turn(), WHITE);
+    assert_eq!(holder.fullmove_number(), 3);
+    assert_eq!(holder.halfmove_clock(), 1);
+    let taken: Vec<_> = holder.moves().map(|m| m.captured).collect();
+    assert_eq!(taken, [NONE, NONE, PAWN, NONE]);
+    let after = holder.board;
+
+    assert_eq!(holder.undo(), Move::from_string("G8F6").ok());
+    assert_eq!(holder.undo(), Move::from_string("E4D5").ok());
+    assert_eq!((holder.turn(), holder.halfmove_clock()), (WHITE, 0));
+    holder.undo();
+    assert_eq!(holder.undo(), Move::from_string("E2E4").ok());
+    assert_eq!(holder.undo(), None);
+    assert_eq!(holder.board, start);
+    assert_eq!(holder.white_list.iter().count(), 16);
+
+    while holder.redo().is_some() {}
+    assert_eq!(holder.board, after);
+    assert_eq!(holder.turn(), WHITE);
+    assert_eq!(holder.black_list.iter().count(), 15);
+
+    // A new move forgets what was taken back.
+    holder.undo();
+    play(&mut holder, &["B8C6"]);
+    assert_eq!(holder.redo(), None);
+    assert_eq!(holder.turn(), WHITE);
+    assert_eq!(holder.with_turn(BLACK).turn(), BLACK);
+}
+
+#[test]
+fn repetition_test() {
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    assert_eq!(holder.repetitions(), 0);
+
+    play(&mut holder, &["G1F3", "G8F6", "F3G1", "F6G8"]);
+    assert_eq!(holder.repetitions(), 1);
+    play(&mut holder, &["G1F3", "G8F6", "F3G1", "F6G8"]);
+    assert_eq!(holder.repetitions(), 2);
+
+    play(&mut holder, &["E2E4"]);
+    assert_eq!(holder.repetitions(), 0);
+    assert_eq!(holder.halfmove_clock(), 0);
+}
@@ -0,0 +1,79 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::book_builder::{BookBuilder, MoveStats};
+use chess_algorithm::database::{DataBaseInstance, Game, MoveRecord};
+use chess_algorithm::figure::Color;
+use chess_algorithm::movement::Move;
+use chess_algorithm::polyglot::{encode_move, polyglot_key, OpeningBook};
+
+fn movies(game: &[&str]) -> Vec<Move> {
+    game.iter().map(|m| Move::from_string(m).unwrap()).collect()
+}
+
+fn start_key() -> u64 {
+    polyglot_key(&ByteBoard::default(), Color::WHITE)
+}
+
+// Fool's mate, white is mated.
+const FOOLS_MATE: [&str; 4] = ["F2F3", "E7E5", "G2G4", "D8H4"];
+
+#[test]
+fn test_game_statistics() {
+    let mut builder = BookBuilder::new(2);
+    assert_eq!(builder.add_game(&movies(&FOOLS_MATE)), Ok(Some(Color::BLACK)));
+    assert_eq!(builder.add_game(&movies(&["F2F3", "D7D5"])), Ok(None));
+    assert_eq!(builder.add_game(&movies(&["E2E4", "E7E5"])), Ok(None));
+    assert!(builder.add_game(&movies(&["E2E5"])).is_err());
+    assert!(builder.add_game(&movies(&["E7E5"])).is_err());
+    assert_eq!(builder.games(), 3);
+
+    let f3 = encode_move(&Move::from_string("F2F3").unwrap());
+    let e4 = encode_move(&Move::from_string("E2E4").unwrap());
+    assert_eq!(builder.stats(start_key()), vec![
+        (e4, MoveStats { count: 1, wins: 0, draws: 1, losses: 0 }),
+        (f3, MoveStats { count: 2, wins: 0, draws: 1, losses: 1 }),
+    ]);
+    // Only the first two plies are counted.
+    assert_eq!(builder.positions(), 3);
+}
+
+#[test]
+fn test_build_book() {
+    let mut builder = BookBuilder::new(10);
+    builder.add_game(&movies(&FOOLS_MATE)).unwrap();
+    builder.add_game(&movies(&FOOLS_MATE)).unwrap();
+    builder.add_game(&movies(&["E2E4", "E7E5"])).unwrap();
+
+    let book = builder.build(1);
+    // White's losing movies are left out, black's winning ones weigh two per game.
+    let start = book.entries(start_key());
+    assert_eq!(start.len(), 1);
+    assert_eq!(start[0].raw_move, encode_move(&Move::from_string("E2E4").unwrap()));
+    assert_eq!(book.len(), 4);
+
+    let mut bytes = Vec::new();
+    book.write(&mut bytes).unwrap();
+    let loaded = OpeningBook::read(&mut bytes.as_slice()).unwrap();
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    let movement = loaded.choose(&holder.controller(Color::WHITE), 7).unwrap();
+    assert_eq!(movement.to, Move::from_string("E2E4").unwrap().to);
+
+    assert_eq!(builder.build(2).len(), 2);
+}
+
+#[test]
+fn test_add_database() {
+    let mut db = DataBaseInstance::new(":memory:").unwrap();
+    db.create_tables().unwrap();
+    let game = db.add_game(Game::now()).unwrap();
+    let mut record = MoveRecord::new(&game);
+    for movement in movies(&FOOLS_MATE) {
+        record = record.to_next(&movement);
+        db.add_move(&record).unwrap();
+    }
+
+    let mut builder = BookBuilder::new(10);
+    assert_eq!(builder.add_database(&db), Ok(1));
+    let f3 = encode_move(&Move::from_string("F2F3").unwrap());
+    assert_eq!(builder.stats(start_key()), vec![(f3, MoveStats { count: 1, wins: 0, draws: 0, losses: 1 })]);
+}
@@ -1,5 +1,8 @@
 use chess_algorithm::database::{DataBaseInstance, Game, DEFAULT_PATH, MoveRecord};
-use chess_algorithm::movement::MoveType::SIMPLE;
+use chess_algorithm::movement::MoveType::{SIMPLE, TRANSFORM};
+use chess_algorithm::movement::Move;
+use chess_algorithm::point::Point;
+use rusqlite::{params, Connection};
 
 #[test]
 fn test_open_db() {
@@ -34,9 +37,7 @@ fn test_add_move_record() {
     let move_record = MoveRecord {
         game_id: 1,
         move_number: 0,
-        p_from: "c1".to_string(),
-        p_to: "c2".to_string(),
-        m_type: SIMPLE.to_string()
+        packed: Move::new(Point::from_string("c1").unwrap(), Point::from_string("c2").unwrap(), SIMPLE).pack()
     };
     let instance = DataBaseInstance::default();
     match instance.add_move(&move_record) {
@@ -45,4 +46,53 @@ fn test_add_move_record() {
         }
         Ok(_) => {}
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_remove_moves_from() {
+    let mut instance = DataBaseInstance::new(":memory:").unwrap();
+    instance.create_tables().unwrap();
+    let game = instance.add_game(Game::now()).unwrap();
+
+    let mut record = MoveRecord::new(&game);
+    for movement in ["E2E4", "E7E5", "G1F3"] {
+        record = record.to_next(&Move::from_string(movement).unwrap());
+        instance.add_move(&record).unwrap();
+    }
+
+    assert_eq!(instance.remove_moves_from(&game, 1), Ok(2));
+    let records = instance.find_moves(&game).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].to_move().unwrap(), Move::from_string("E2E4").unwrap());
+}
+
+#[test]
+fn test_migrate_string_move_records() {
+    let path = std::env::temp_dir().join("chess_algorithm_migration_test.db");
+    let _ = std::fs::remove_file(&path);
+    let path = path.to_str().unwrap();
+
+    let connection = Connection::open(path).unwrap();
+    connection.execute_batch(
+        "CREATE TABLE game (id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, start_time DATETIME NOT NULL);
+        CREATE TABLE move_record (game_id INTEGER, move_number INTEGER, p_from CHAR(4), p_to CHAR(4), type CHAR(10));
+        INSERT INTO game (start_time) VALUES ('2020-01-01T00:00:00Z');
+        INSERT INTO move_record VALUES (1, 0, 'E2', 'E4', 'SIMPLE');
+        INSERT INTO move_record VALUES (1, 1, 'A7', 'A8', 'TRANSFORM');"
+    ).unwrap();
+    drop(connection);
+
+    let mut instance = DataBaseInstance::new(path).unwrap();
+    instance.create_tables().unwrap();
+    instance.create_tables().unwrap();
+    let game = instance.find_game(1).unwrap();
+    let movies = instance.find_moves(&game).unwrap().iter().map(|r| r.to_move().unwrap()).collect::<Vec<_>>();
+    let promotion = Move::new(Point::from_string("A7").unwrap(), Point::from_string("A8").unwrap(), TRANSFORM);
+    assert_eq!(movies, vec![Move::from_string("E2E4").unwrap(), promotion]);
+
+    let columns: i32 = Connection::open(path).unwrap()
+        .query_row("SELECT COUNT(*) FROM pragma_table_info('move_record')", params![], |row| row.get(0))
+        .unwrap();
+    assert_eq!(columns, 3);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,74 @@
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::endgame::{adjust, Material, SCALE_NORMAL, SCALE_OPPOSITE_BISHOPS};
+use chess_algorithm::evaluation::TaperedEvaluator;
+use chess_algorithm::fen;
+use chess_algorithm::figure::{Color, W_KING};
+use chess_algorithm::point::Point;
+use chess_algorithm::score::{AlphaBetaSearch, Evaluator, MoveSearch};
+
+fn white_adjust(fen: &str, score: i32) -> i32 {
+    let (board, _) = fen::parse(fen).unwrap();
+    let holder = BoardDataHolder::new(&board);
+    adjust(&holder.board, &holder.white_list, &holder.black_list, score)
+}
+
+fn white_evaluation(fen: &str) -> i32 {
+    let (board, _) = fen::parse(fen).unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    TaperedEvaluator::default().evaluate(&holder.controller(Color::WHITE))
+}
+
+#[test]
+fn test_material_signature() {
+    let (board, _) = fen::parse("4k3/8/8/8/8/8/2PP4/1RB1K3 w - - 0 1").unwrap();
+    let holder = BoardDataHolder::new(&board);
+    assert_eq!(Material::new(&holder.board, &holder.white_list).to_string(), "KRBPP");
+    assert!(Material::new(&holder.board, &holder.black_list).is_bare_king());
+}
+
+#[test]
+fn test_weak_king_is_driven_to_edge() {
+    let center = white_evaluation("8/8/8/4k3/8/8/8/R3K3 w - - 0 1");
+    let edge = white_evaluation("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    assert!(edge > center);
+
+    let right_corner = white_adjust("7k/8/5K2/8/8/8/8/2B1N3 w - - 0 1", 0);
+    let wrong_corner = white_adjust("k7/8/2K5/8/8/8/8/2B1N3 w - - 0 1", 0);
+    assert!(right_corner > wrong_corner);
+}
+
+#[test]
+fn test_drawish_endings_are_scaled() {
+    assert_eq!(white_adjust("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", 3000), 0);
+    assert!(white_adjust("4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1", 6000) < 1000);
+    assert_eq!(white_adjust("k7/8/8/8/P7/8/8/2B1K3 w - - 0 1", 6400), 200);
+    assert_eq!(white_adjust("k7/8/8/8/P7/8/8/3BK3 w - - 0 1", 6400), 6400);
+
+    let opposite = white_adjust("4k3/3b4/8/8/8/2P5/1PP5/2B1K3 w - - 0 1", 6400);
+    assert_eq!(opposite, 6400 * SCALE_OPPOSITE_BISHOPS / SCALE_NORMAL);
+    assert_eq!(white_adjust("4k3/2b5/8/8/8/2P5/1PP5/2B1K3 w - - 0 1", 6400), 6400);
+}
+
+#[test]
+fn test_adjust_is_symmetric() {
+    assert_eq!(white_adjust("4k3/8/8/8/8/8/8/2b1K3 w - - 0 1", -3000), 0);
+
+    let (board, _) = fen::parse("8/8/8/3k4/8/8/8/R3K3 w - - 0 1").unwrap();
+    let holder = BoardDataHolder::new(&board);
+    let white = adjust(&holder.board, &holder.white_list, &holder.black_list, 5000);
+    let black = adjust(&holder.board, &holder.black_list, &holder.white_list, -5000);
+    assert!(white > 5000);
+    assert_eq!(black, - white);
+}
+
+#[test]
+fn test_king_capture_is_not_scaled() {
+    assert_eq!(white_adjust("4k3/8/8/8/8/3n4/8/4K1N1 w - - 0 1", W_KING), W_KING);
+    assert_eq!(white_adjust("4k3/8/8/8/8/3n4/8/4K1N1 w - - 0 1", - W_KING), - W_KING);
+
+    let (board, color) = fen::parse("4k3/8/8/8/8/3n4/8/4K1N1 w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let (score, best_move) = AlphaBetaSearch::default().find_best_move(&mut holder.controller(color), 4);
+    assert!(score > - W_KING / 2);
+    assert_eq!(best_move.unwrap().from, Point::from_string("E1").unwrap());
+}
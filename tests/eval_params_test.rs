@@ -0,0 +1,59 @@
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::eval_params::{EvalParams, DEFAULT_PARAMS};
+use chess_algorithm::evaluation::{TaperedEvaluator, TaperedScore};
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color::WHITE;
+use chess_algorithm::figure::Rank::KNIGHT;
+use chess_algorithm::pst::PST_SCALE;
+use chess_algorithm::score::Evaluator;
+
+#[test]
+fn test_text_round_trip() {
+    let mut params = EvalParams::default();
+    params.piece_values[KNIGHT as usize] = 325;
+    params.bishop_pair = TaperedScore::new(40, 60);
+    params.pst_eg[KNIGHT as usize][27] = -7;
+
+    let text = params.to_string();
+    assert_eq!(text.parse::<EvalParams>(), Ok(params.clone()));
+    assert_eq!(EvalParams::default().values().len(), params.values().len());
+    assert_ne!(EvalParams::default().values(), params.values());
+}
+
+#[test]
+fn test_partial_and_malformed_files() {
+    let text = "# knights are worth more\nmaterial = [900, 500, 400, 350, 100]\npawns.passed = [\n  5, 10, 5, 15,\n  10, 25, 20, 45, 35, 75, 60, 120,\n]\n";
+    let params = text.parse::<EvalParams>().unwrap();
+    assert_eq!(params.piece_values[KNIGHT as usize], 350);
+    assert_eq!(params.passed, DEFAULT_PARAMS.passed);
+    assert_eq!(params.pst_mg, DEFAULT_PARAMS.pst_mg);
+
+    assert!("material = [900, 500]".parse::<EvalParams>().is_err());
+    assert!("unknown = 1".parse::<EvalParams>().is_err());
+    assert!("king_safety.shield_near = x".parse::<EvalParams>().is_err());
+}
+
+#[test]
+fn test_evaluator_uses_params() {
+    let (board, _) = fen::parse("4k3/8/8/8/8/8/4P3/1N2K3 w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let mut params = EvalParams::default();
+    params.piece_values[KNIGHT as usize] += 50;
+
+    let default_score = TaperedEvaluator::default().evaluate(&holder.controller(WHITE));
+    let score = TaperedEvaluator::with_params(params).evaluate(&holder.controller(WHITE));
+    assert_eq!(score - default_score, 50 * PST_SCALE);
+}
+
+#[test]
+fn test_sections() {
+    let text = "material = [900, 500, 400, 300, 100]\n\n[king_safety]\nshield_near = 20\nattacker_weight = [6, 3, 2, 2]\n\n[mobility]\nbishop_pair = [35, 55]\n";
+    let params = text.parse::<EvalParams>().unwrap();
+    assert_eq!(params.shield_near, 20);
+    assert_eq!(params.attacker_weight[KNIGHT as usize], 2);
+    assert_eq!(params.bishop_pair, TaperedScore::new(35, 55));
+
+    let saved = DEFAULT_PARAMS.to_string();
+    assert!(saved.contains("\n[pst_mg]\nking = [\n"), "{}", saved);
+    assert!("[king_safety\nshield_near = 20".parse::<EvalParams>().is_err());
+}
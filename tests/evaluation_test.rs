@@ -0,0 +1,78 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::eval_params::{EvalParams, DEFAULT_PARAMS};
+use chess_algorithm::evaluation::{game_phase, TaperedEvaluator, TaperedScore, MAX_PHASE};
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Figure;
+use chess_algorithm::figure::Rank::{KING, KNIGHT, PAWN};
+use chess_algorithm::point::Point;
+use chess_algorithm::pst::pst_score;
+use chess_algorithm::score::Evaluator;
+
+fn point(s: &str) -> Point {
+    Point::from_string(s).unwrap()
+}
+
+#[test]
+fn test_start_position() {
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    assert_eq!(game_phase(&holder.board, &holder.white_list, &holder.black_list), MAX_PHASE);
+    assert_eq!(TaperedEvaluator::default().evaluate(&holder.controller(WHITE)), 0);
+    assert_eq!(TaperedEvaluator::default().evaluate(&holder.controller(BLACK)), 0);
+}
+
+#[test]
+fn test_bare_kings_are_endgame() {
+    let (board, _) = fen::parse("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+    let holder = BoardDataHolder::new(&board);
+    assert_eq!(game_phase(&holder.board, &holder.white_list, &holder.black_list), 0);
+    assert_eq!(TaperedScore::new(100, -40).taper(0), -40);
+    assert_eq!(TaperedScore::new(100, -40).taper(MAX_PHASE), 100);
+}
+
+#[test]
+fn test_pst_is_mirrored_for_black() {
+    assert_eq!(pst_score(&DEFAULT_PARAMS, point("E4"), Figure::new(PAWN, WHITE, false)), pst_score(&DEFAULT_PARAMS, point("E5"), Figure::new(PAWN, BLACK, false)));
+    assert_eq!(pst_score(&DEFAULT_PARAMS, point("G1"), Figure::new(KING, WHITE, false)), pst_score(&DEFAULT_PARAMS, point("G8"), Figure::new(KING, BLACK, false)));
+    assert_ne!(pst_score(&DEFAULT_PARAMS, point("D2"), Figure::new(PAWN, WHITE, false)), pst_score(&DEFAULT_PARAMS, point("D7"), Figure::new(PAWN, WHITE, false)));
+}
+
+#[test]
+fn test_knight_prefers_center() {
+    let knight = Figure::new(KNIGHT, WHITE, false);
+    assert!(pst_score(&DEFAULT_PARAMS, point("D4"), knight).mg > pst_score(&DEFAULT_PARAMS, point("A1"), knight).mg);
+    assert!(pst_score(&DEFAULT_PARAMS, point("E5"), knight).eg > pst_score(&DEFAULT_PARAMS, point("H8"), knight).eg);
+}
+
+#[test]
+fn test_trace_matches_evaluation() {
+    let (board, _) = fen::parse("r1bq1rk1/pp3ppp/2n1pn2/3p4/1bPP4/2N1PN2/PP1B1PPP/R2QKB1R w KQ - 0 8").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let evaluator = TaperedEvaluator::default();
+
+    for color in [WHITE, BLACK] {
+        let controller = holder.controller(color);
+        let trace = evaluator.trace(&controller);
+        assert_eq!(trace.score(color), evaluator.evaluate(&controller));
+        assert_eq!(trace.score(color), - trace.score(color.invert()));
+    }
+
+    let table = evaluator.trace(&holder.controller(WHITE)).to_string();
+    for term in ["Material", "PST", "Pawns", "King safety", "Mobility"] {
+        assert!(table.contains(term), "{}", table);
+    }
+}
+
+#[test]
+fn test_set_params_clears_pawn_table() {
+    let (board, _) = fen::parse("4k3/p7/1p6/8/8/P7/P7/4K3 w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let mut evaluator = TaperedEvaluator::default();
+    evaluator.evaluate(&holder.controller(WHITE));
+
+    let params = EvalParams { doubled: TaperedScore::new(-2000, -2000), ..EvalParams::default() };
+    evaluator.set_params(params.clone());
+    let expected = TaperedEvaluator::with_params(params).evaluate(&holder.controller(WHITE));
+    assert_eq!(evaluator.evaluate(&holder.controller(WHITE)), expected);
+}
@@ -1,13 +1,81 @@
 extern crate chess_algorithm;
 
-use std::cmp::Ordering;
 use chess_algorithm::board::*;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::fen;
 use chess_algorithm::figure::*;
 use chess_algorithm::figure_list::*;
 use chess_algorithm::figure::Color::{WHITE, NONE};
+use chess_algorithm::movement::{Move, MoveType};
+use chess_algorithm::perft::perft;
 
 use chess_algorithm::point::Point;
 
+const MIDDLE_GAME: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w - - 0 1";
+
+fn points<L: PieceList>(list: &L) -> Vec<Point> {
+    list.iter().collect()
+}
+
+fn new_list<L: PieceList>(board: &ByteBoard) -> L {
+    let mut list = L::default();
+    list.fill(board, WHITE);
+    list
+}
+
+/// Checks shared by every `PieceList` implementation.
+fn check_conformance<L: PieceList>() {
+    let mut board = ByteBoard::default();
+    let mut list: L = new_list(&board);
+    let order = points(&list);
+    assert_eq!(order.len(), 16);
+    assert!(order.is_sorted_by(|a, b| board.point(*b).weight() <= board.point(*a).weight()));
+
+    // Removals are undone in reverse order and the order comes back.
+    let (first, second) = (Point::new(2, 1), Point::new(5, 0));
+    let removed_figure = *board.point(first);
+    *board.point_mut(first) = Figure::new(Rank::NONE, NONE, false);
+    list.remove(first);
+    list.remove(second);
+    assert_eq!(list.iter().count(), 14);
+    assert!(!points(&list).contains(&first));
+    list.restore(second);
+    assert_eq!(list.iter().map(|p| board.point(p).weight()).sum::<i32>(), 130000);
+    list.restore(first);
+    *board.point_mut(first) = removed_figure;
+    assert_eq!(list.iter().map(|p| board.point(p).weight()).sum::<i32>(), 131000);
+    assert_eq!(points(&list), order);
+
+    let movement = Move::new(Point::new(1, 0), Point::new(2, 2), MoveType::SIMPLE);
+    list.make_move(&movement);
+    assert!(points(&list).contains(&movement.to) && !points(&list).contains(&movement.from));
+    list.unmake_move(&movement);
+    assert_eq!(points(&list), order);
+
+    list.swap(Point::new(0, 0), Point::new(3, 0));
+    let swapped = points(&list);
+    assert_eq!(swapped.iter().position(|p| *p == Point::new(3, 0)), order.iter().position(|p| *p == Point::new(0, 0)));
+    list.swap(Point::new(0, 0), Point::new(3, 0));
+    assert_eq!(points(&list), order);
+
+    // Refilling replaces the content.
+    let (board, _) = fen::parse(MIDDLE_GAME).unwrap();
+    list.fill(&board, WHITE);
+    assert_eq!(points(&list), points(&new_list::<L>(&board)));
+}
+
+/// Perft through a controller keeping its figures in `L` lists, which must be unchanged after it.
+fn controller_perft<L: PieceList>(fen: &str, depth: u32) -> u64 {
+    let (board, color) = fen::parse(fen).unwrap();
+    let mut holder: BoardDataHolder<ByteBoard, L> = BoardDataHolder::with_lists(&board);
+    let (white, black) = (points(&holder.white_list), points(&holder.black_list));
+    let nodes = perft(&mut holder.controller(color), depth);
+    assert_eq!(points(&holder.white_list), white);
+    assert_eq!(points(&holder.black_list), black);
+    assert_eq!(holder.board, board);
+    nodes
+}
+
 #[test]
 fn test_fill_is_descending_sort() {
     let board = ByteBoard::default();
@@ -20,27 +88,35 @@ fn test_fill_is_descending_sort() {
 }
 
 #[test]
-fn test_several_iterators() {
-    let mut board = ByteBoard::default();
-    let mut list = FigurePointList::new(&board, WHITE);
+fn test_conformance() {
+    check_conformance::<FigurePointList>();
+    check_conformance::<FigureArrayList>();
+    check_conformance::<FigureLinkedList>();
+}
 
-    // let movement = Move { from: Point::new(1, 1), to: Point::new(1, 2) };
-    let point = Point::new(2, 1);
+#[test]
+fn test_slots() {
+    let board = ByteBoard::default();
+    let mut list = FigurePointList::new(&board, WHITE);
+    let original = list;
 
-    // board.make_move(&movement);
-    // let movement_info = list.make_move(&movement);
-    let removed_figure = *board.point(point);
-    *board.point_mut(point) = Figure::new(Rank::NONE, NONE, false);
-    let mut cursor = list.node_iter().skip_while(|lnc| lnc.point() != point).next().unwrap();
-    cursor.remove();
+    let (from, to) = (Point::new(1, 0), Point::new(2, 2));
+    let slot = list.move_point(from, to).unwrap();
+    assert_eq!(list.slot(to), Some(slot));
+    assert_eq!(list.slot(from), None);
+    assert_eq!(list.move_point(Point::new(4, 4), to), None);
 
-    assert_eq!(list.node_iter().count(), 15);
-    assert_eq!(list.iter().map(|p| board.point(p).weight()).sum::<i32>(), 130000);
+    list.place(slot, from);
+    assert!(list == original);
+}
 
-    // FigureList::unmake_move(&movement, movement_info);
-    cursor.restore();
-    *board.point_mut(point) = removed_figure;
+#[test]
+fn test_controller_with_every_list() {
+    let expected = controller_perft::<FigurePointList>(MIDDLE_GAME, 2);
+    assert_eq!(controller_perft::<FigureArrayList>(MIDDLE_GAME, 2), expected);
+    assert_eq!(controller_perft::<FigureLinkedList>(MIDDLE_GAME, 2), expected);
 
-    assert_eq!(list.node_iter().count(), 16);
-    assert_eq!(list.iter().map(|p| board.point(p).weight()).sum::<i32>(), 131000);
-}
\ No newline at end of file
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1";
+    assert_eq!(controller_perft::<FigureArrayList>(start, 3), 8902);
+    assert_eq!(controller_perft::<FigureLinkedList>(start, 3), 8902);
+}
@@ -0,0 +1,34 @@
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::eval_params::DEFAULT_PARAMS;
+use chess_algorithm::evaluation::TaperedScore;
+use chess_algorithm::fen;
+use chess_algorithm::king_safety::{king_safety, ATTACK_UNIT_PENALTY, ATTACKER_WEIGHT, OPEN_FILE, SHIELD_MISSING, SHIELD_NEAR};
+use chess_algorithm::figure::Rank::{QUEEN, ROOK};
+use chess_algorithm::pst::PST_SCALE;
+
+fn white_king_safety(fen: &str) -> TaperedScore {
+    let (board, _) = fen::parse(fen).unwrap();
+    let holder = BoardDataHolder::new(&board);
+    king_safety(&DEFAULT_PARAMS, &holder.board, &holder.white_list, &holder.black_list)
+}
+
+#[test]
+fn test_pawn_shield() {
+    let safety = white_king_safety("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    assert_eq!(safety, TaperedScore::new(SHIELD_NEAR * 3 * PST_SCALE, 0));
+}
+
+#[test]
+fn test_open_file_near_king() {
+    let safety = white_king_safety("6k1/5p1p/8/8/8/8/5P1P/6K1 w - - 0 1");
+    let expected = SHIELD_NEAR * 2 + SHIELD_MISSING + OPEN_FILE;
+    assert_eq!(safety, TaperedScore::new(expected * PST_SCALE, 0));
+}
+
+#[test]
+fn test_attackers_of_king_zone() {
+    let safety = white_king_safety("6k1/5ppp/8/8/7q/8/5PPP/r5K1 w - - 0 1");
+    let units = ATTACKER_WEIGHT[ROOK as usize] + ATTACKER_WEIGHT[QUEEN as usize];
+    let expected = SHIELD_NEAR * 3 + ATTACK_UNIT_PENALTY * units * units;
+    assert_eq!(safety, TaperedScore::new(expected * PST_SCALE, 0));
+}
@@ -0,0 +1,55 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Figure;
+use chess_algorithm::figure::Rank::{KING, QUEEN};
+use chess_algorithm::lazy_smp::LazySmpSearch;
+use chess_algorithm::point::Point;
+use chess_algorithm::position::PositionBuilder;
+use chess_algorithm::score::{AlphaBetaSearch, MoveSearch};
+
+fn queen_hanging_board() -> ByteBoard {
+    PositionBuilder::new()
+        .with_figure(Point::new(0, 0), Figure::new(KING, WHITE, false))
+        .with_figure(Point::new(1, 1), Figure::new(QUEEN, WHITE, false))
+        .with_figure(Point::new(7, 6), Figure::new(KING, BLACK, false))
+        .with_figure(Point::new(1, 5), Figure::new(QUEEN, BLACK, false))
+        .build()
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_lazy_smp_takes_hanging_queen() {
+    let mut holder = BoardDataHolder::new(&queen_hanging_board());
+    let mut controller = holder.controller(WHITE);
+
+    let (_, best_move) = LazySmpSearch::new(4).find_best_move(&mut controller, 3);
+
+    assert_eq!(best_move.unwrap().to, Point::new(1, 5));
+    assert!(controller.position_counter > 0);
+}
+
+#[test]
+fn test_lazy_smp_agrees_with_alpha_betta() {
+    let mut holder = BoardDataHolder::new(&queen_hanging_board());
+    let (ab_score, _) = AlphaBetaSearch::default().find_best_move(&mut holder.controller(WHITE), 3);
+    let (smp_score, _) = LazySmpSearch::new(2).find_best_move(&mut holder.controller(WHITE), 3);
+
+    assert_eq!(ab_score, smp_score);
+    assert_eq!(holder.board, queen_hanging_board());
+}
+
+#[test]
+fn test_lazy_smp_search_settings() {
+    let mut holder = BoardDataHolder::new(&queen_hanging_board());
+    let search = AlphaBetaSearch { aspiration_window: 0, max_extension: 0, ..AlphaBetaSearch::default() };
+    let mut ab_controller = holder.controller(WHITE);
+    let ab = search.find_best_move(&mut ab_controller, 3);
+    let ab_nodes = ab_controller.position_counter;
+
+    let smp_search = LazySmpSearch::new(1).with_aspiration_window(0).with_max_extension(0);
+    let mut smp_controller = holder.controller(WHITE);
+    assert_eq!(smp_search.find_best_move(&mut smp_controller, 3), ab);
+    assert_eq!(smp_controller.position_counter, ab_nodes);
+}
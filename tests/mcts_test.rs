@@ -0,0 +1,48 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Figure;
+use chess_algorithm::figure::Rank::{KING, QUEEN};
+use chess_algorithm::mcts::{MctsSearch, Rollout};
+use chess_algorithm::point::Point;
+use chess_algorithm::position::PositionBuilder;
+use chess_algorithm::score::MoveSearch;
+
+fn queen_hanging_board() -> ByteBoard {
+    PositionBuilder::new()
+        .with_figure(Point::new(0, 0), Figure::new(KING, WHITE, false))
+        .with_figure(Point::new(1, 1), Figure::new(QUEEN, WHITE, false))
+        .with_figure(Point::new(7, 6), Figure::new(KING, BLACK, false))
+        .with_figure(Point::new(1, 5), Figure::new(QUEEN, BLACK, false))
+        .build()
+        .unwrap()
+        .0
+}
+
+#[test]
+fn test_mcts_evaluation_takes_hanging_queen() {
+    let mut holder = BoardDataHolder::new(&queen_hanging_board());
+    let search = MctsSearch { iterations: 3000, time_limit: None, ..MctsSearch::default() };
+
+    let (score, best_move) = search.find_best_move(&mut holder.controller(WHITE), 0);
+
+    assert_eq!(best_move.unwrap().to, Point::new(1, 5));
+    assert!(score > 0);
+    assert_eq!(holder.board, queen_hanging_board());
+}
+
+#[test]
+fn test_mcts_playout_captures_king() {
+    let mut board = queen_hanging_board();
+    *board.cell_mut(1, 5) = Figure::empty();
+    *board.cell_mut(1, 6) = Figure::new(KING, BLACK, false);
+    *board.cell_mut(7, 6) = Figure::empty();
+
+    let mut holder = BoardDataHolder::new(&board);
+    let search = MctsSearch { iterations: 500, time_limit: None, rollout: Rollout::PLAYOUT(20), ..MctsSearch::default() };
+
+    let (_, best_move) = search.find_best_move(&mut holder.controller(WHITE), 0);
+
+    assert_eq!(best_move.unwrap().to, Point::new(1, 6));
+    assert_eq!(holder.board, board);
+}
@@ -0,0 +1,44 @@
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::eval_params::DEFAULT_PARAMS;
+use chess_algorithm::evaluation::TaperedScore;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Rank::{BISHOP, KING, KNIGHT, ROOK};
+use chess_algorithm::mobility::{mobility, BISHOP_PAIR, CONNECTED_ROOKS, KNIGHT_OUTPOST, MOBILITY, ROOK_OPEN_FILE, ROOK_SEVENTH_RANK};
+use chess_algorithm::pst::PST_SCALE;
+
+fn white_mobility(fen: &str) -> TaperedScore {
+    let (board, _) = fen::parse(fen).unwrap();
+    let holder = BoardDataHolder::new(&board);
+    mobility(&DEFAULT_PARAMS, &holder.board, &holder.white_list, &holder.black_list)
+}
+
+#[test]
+fn test_enemy_pawn_attacks_are_not_counted() {
+    let free = white_mobility("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+    let covered = white_mobility("4k3/8/8/8/8/3p4/8/N3K3 w - - 0 1");
+
+    let king_cells = MOBILITY[KING as usize] * 5;
+    assert_eq!(free, (MOBILITY[KNIGHT as usize] * 2 + king_cells) * PST_SCALE);
+    assert_eq!(covered, (MOBILITY[KNIGHT as usize] + king_cells) * PST_SCALE);
+}
+
+#[test]
+fn test_rooks_on_seventh_and_open_file() {
+    let score = white_mobility("7k/R6R/8/8/8/8/8/4K3 b - - 0 1");
+    let rook_cells = MOBILITY[ROOK as usize] * (1 + 6 + 6 + 1 + 6 + 6);
+    let king_cells = MOBILITY[KING as usize] * 5;
+    let bonuses = ROOK_OPEN_FILE * 2 + ROOK_SEVENTH_RANK * 2 + CONNECTED_ROOKS;
+    assert_eq!(score, (rook_cells + king_cells + bonuses) * PST_SCALE);
+}
+
+#[test]
+fn test_bishop_pair_and_knight_outpost() {
+    let pair = white_mobility("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+    let single = white_mobility("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1");
+    let second_bishop = MOBILITY[BISHOP as usize] * 7 - MOBILITY[KING as usize];
+    assert_eq!(pair - single, (second_bishop + BISHOP_PAIR) * PST_SCALE);
+
+    let outpost = white_mobility("4k3/8/8/3N4/4P3/8/8/4K3 w - - 0 1");
+    let chased = white_mobility("4k3/4p3/8/3N4/4P3/8/8/4K3 w - - 0 1");
+    assert_eq!(outpost - chased, (KNIGHT_OUTPOST + MOBILITY[KNIGHT as usize]) * PST_SCALE);
+}
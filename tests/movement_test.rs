@@ -3,11 +3,12 @@
 extern crate chess_algorithm;
 
 use chess_algorithm::movement::*;
-use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board::{Board, ByteBoard};
+use chess_algorithm::fen;
 use chess_algorithm::figure::{Figure, Color};
-use chess_algorithm::figure::Rank::{KING, PAWN, QUEEN, ROOK, NONE};
+use chess_algorithm::figure::Rank::{KING, KNIGHT, PAWN, QUEEN, ROOK, NONE};
 use chess_algorithm::figure::Color::{WHITE, BLACK};
-use chess_algorithm::figure_list::{FigurePointList};
+use chess_algorithm::figure_list::{FigurePointList, PieceList};
 use std::collections::HashSet;
 use chess_algorithm::point::Point;
 use chess_algorithm::board_controller::BoardDataHolder;
@@ -139,7 +140,7 @@ fn test_rook_eat() {
     *fixture.board.cell_mut(1, 6) = Figure::new(PAWN, BLACK, false);
     let movies: HashSet<Move> = fixture.generate_figure_movies(1, 1).iter().map(|m| *m).collect();
 
-    assert!(movies.contains(&Move { from: Point::new(1, 1), to: Point::new(1, 6), m_type: MoveType::SIMPLE }));
+    assert!(movies.contains(&Move::new(Point::new(1, 1), Point::new(1, 6), MoveType::SIMPLE)));
 }
 
 #[test]
@@ -149,7 +150,7 @@ fn test_generate_queen_take() {
     *fixture.board.cell_mut(1, 6) = Figure::new(PAWN, BLACK, false);
     let movies: HashSet<Move> = fixture.generate_figure_movies(1, 1).iter().map(|m| *m).collect();
 
-    assert!(movies.contains(&Move { from: Point::new(1, 1), to: Point::new(1, 6), m_type: MoveType::SIMPLE }));
+    assert!(movies.contains(&Move::new(Point::new(1, 1), Point::new(1, 6), MoveType::SIMPLE)));
 }
 
 #[test]
@@ -189,11 +190,7 @@ fn test_pawn_transform() {
     let mut expected_movies = HashSet::new();
     let from_p = Point::new(1, 6);
 
-    expected_movies.insert(Move {
-        from: from_p,
-        to: Point::new(1, 7),
-        m_type: TRANSFORM
-    });
+    expected_movies.insert(Move::new(from_p, Point::new(1, 7), TRANSFORM));
 
     let mut fixture = MovementFixture::new();
     *fixture.board.cell_mut(1, 6) = Figure::new(PAWN, WHITE, false);
@@ -206,6 +203,69 @@ fn test_pawn_transform() {
     assert_eq!(movies, expected_movies);
 }
 
+#[test]
+fn test_generated_captures() {
+    let mut fixture = MovementFixture::new();
+    *fixture.board.cell_mut(1, 1) = Figure::new(ROOK, WHITE, false);
+    *fixture.board.cell_mut(1, 6) = Figure::new(PAWN, BLACK, false);
+    let movies: Vec<Move> = fixture.generate_figure_movies(1, 1).iter().copied().collect();
+
+    let take = movies.iter().find(|m| m.to == Point::new(1, 6)).unwrap();
+    assert!(take.is_capture());
+    assert_eq!(take.captured, PAWN);
+    assert_eq!(movies.iter().filter(|m| m.is_capture()).count(), 1);
+}
+
+#[test]
+fn test_pack_move() {
+    let quiet = Move::new(Point::new(3, 1), Point::new(3, 3), MoveType::SIMPLE);
+    assert_eq!(quiet.pack(), 11 | (27 << 6));
+    assert_eq!(Move::unpack(0), None);
+
+    let movies = [
+        quiet,
+        Move::new(Point::new(0, 0), Point::new(3, 0), MoveType::SWAP),
+        Move::new(Point::new(3, 0), Point::new(1, 0), MoveType::SIMPLE).with_flags(Move::CASTLE),
+        Move::new(Point::new(4, 4), Point::new(3, 5), MoveType::SIMPLE).with_flags(Move::EN_PASSANT).with_capture(PAWN),
+        Move::new(Point::new(1, 6), Point::new(1, 7), TRANSFORM),
+        Move::new(Point::new(1, 6), Point::new(1, 7), TRANSFORM).with_promotion(KNIGHT),
+    ];
+    let packed: HashSet<u16> = movies.iter().map(|m| m.pack()).collect();
+    assert_eq!(packed.len(), movies.len());
+    for movement in movies.iter() {
+        let unpacked = Move::unpack(movement.pack()).unwrap();
+        assert_eq!(unpacked, *movement);
+        assert_eq!((unpacked.flags, unpacked.promotion), (movement.flags, movement.promotion));
+    }
+
+    // The captured figure is left to the board.
+    assert_eq!(quiet.with_capture(QUEEN), quiet);
+}
+
+#[test]
+fn test_attack_queries() {
+    let (board, _) = fen::parse("4k3/8/8/8/3r4/1NP2P2/4P3/4K3 w - - 0 1").unwrap();
+    let (d4, e4, e3, e2) = (Point::new(4, 3), Point::new(3, 3), Point::new(3, 2), Point::new(3, 1));
+
+    // The f3 pawn covers e4, the d4 rook too.
+    assert_eq!(board.attackers(e4, WHITE), 1 << (2 * 8 + 2));
+    assert!(board.is_square_attacked(e4, BLACK));
+    assert!(!board.is_square_attacked(e3, BLACK));
+    // The knight and the c3 pawn attack the undefended rook, the king defends e2.
+    assert_eq!(attack_counts(&board, d4), (2, 0));
+    assert_eq!(attack_counts(&board, e2), (0, 1));
+
+    let map = AttackMap::new(&board, WHITE);
+    assert_eq!(map.count(d4), 2);
+    assert_eq!(map.count(e2), 1);
+    assert!(!map.is_attacked(Point::new(4, 7)));
+    assert_eq!(map.mask().count_ones() as usize, (0..64).filter(|i| map.is_attacked(Point::new(i % 8, i / 8))).count());
+
+    let mut white_list = FigurePointList::default();
+    white_list.fill(&board, WHITE);
+    assert_eq!(MoveGenerator::new(&board, &white_list).attack_map(), map);
+}
+
 #[test]
 fn test_is_movement_list_descending_sort() {
     let mut fixture = MovementFixture::new();
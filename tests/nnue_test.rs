@@ -0,0 +1,92 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::{BoardController, BoardDataHolder};
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Figure;
+use chess_algorithm::figure::Rank::{KING, ROOK};
+use chess_algorithm::figure::W_KING;
+use chess_algorithm::nnue::{Network, NnueEvaluator};
+use chess_algorithm::point::Point;
+use chess_algorithm::score::{AlphaBetaSearch, Evaluator, MoveSearch};
+
+fn evaluate_from_scratch(evaluator: &NnueEvaluator, controller: &BoardController) -> i32 {
+    let mut holder = BoardDataHolder::new(controller.board());
+    evaluator.evaluate(&holder.controller(controller.friend_color()))
+}
+
+fn check_accumulator(evaluator: &NnueEvaluator, controller: &mut BoardController, depth: i32) {
+    assert_eq!(evaluator.evaluate(controller), evaluate_from_scratch(evaluator, controller));
+    if depth == 0 { return }
+
+    for movement in controller.friend_movies().iter() {
+        let info = controller.make_move(movement);
+        controller.pass_move_to_enemy();
+        check_accumulator(evaluator, controller, depth - 1);
+        controller.pass_move_to_enemy();
+        controller.unmake_move(info);
+    }
+}
+
+#[test]
+fn test_incremental_accumulator() {
+    let evaluator = NnueEvaluator::new(Network::random(16, 7));
+    let (board, color) = fen::parse("r3k3/1P4p1/8/3pP3/2B5/8/6p1/4K2R w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let mut controller = holder.controller(color);
+    evaluator.attach(&mut controller);
+
+    let before = evaluator.evaluate(&controller);
+    check_accumulator(&evaluator, &mut controller, 2);
+    assert_eq!(evaluator.evaluate(&controller), before);
+    assert!(controller.accumulator().is_some());
+}
+
+#[test]
+fn test_weights_file() {
+    let network = Network::random(8, 3);
+    let mut bytes = Vec::new();
+    network.write(&mut bytes).unwrap();
+    assert_eq!(Network::read(&mut bytes.as_slice()).unwrap(), network);
+
+    bytes[0] = b'X';
+    assert!(Network::read(&mut bytes.as_slice()).is_err());
+    assert!(Network::read(&mut &bytes[..100]).is_err());
+}
+
+#[test]
+fn test_search_with_network() {
+    let evaluator = NnueEvaluator::new(Network::random(16, 11));
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    assert_eq!(evaluator.evaluate(&holder.controller(WHITE)), evaluator.evaluate(&holder.controller(BLACK)));
+
+    let search = AlphaBetaSearch::with_evaluator(evaluator);
+    let mut controller = holder.controller(WHITE);
+    let (_, movement) = search.find_best_move(&mut controller, 3);
+    assert!(movement.is_some());
+    assert_eq!(*controller.board(), ByteBoard::default());
+}
+
+#[test]
+fn test_search_with_network_sees_kings() {
+    for seed in [3, 7, 11] {
+        let search = AlphaBetaSearch::with_evaluator(NnueEvaluator::new(Network::random(16, seed)));
+
+        let mut board = ByteBoard::empty();
+        *board.cell_mut(4, 0) = Figure::new(ROOK, WHITE, false);
+        *board.cell_mut(5, 0) = Figure::new(KING, WHITE, false);
+        *board.cell_mut(4, 7) = Figure::new(KING, BLACK, false);
+        let mut holder = BoardDataHolder::new(&board);
+        let (score, movement) = search.find_best_move(&mut holder.controller(WHITE), 2);
+        assert!(score > W_KING / 2);
+        assert_eq!(movement.unwrap().to, Point::new(4, 7));
+
+        let mut board = ByteBoard::empty();
+        *board.cell_mut(4, 0) = Figure::new(KING, WHITE, false);
+        *board.cell_mut(4, 7) = Figure::new(ROOK, BLACK, false);
+        *board.cell_mut(6, 7) = Figure::new(KING, BLACK, false);
+        let mut holder = BoardDataHolder::new(&board);
+        let (score, movement) = search.find_best_move(&mut holder.controller(WHITE), 2);
+        assert!(score > - W_KING / 2);
+        assert_ne!(movement.unwrap().to.x(), 4);
+    }
+}
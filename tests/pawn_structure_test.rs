@@ -0,0 +1,59 @@
+use chess_algorithm::eval_params::DEFAULT_PARAMS;
+use chess_algorithm::evaluation::TaperedScore;
+use chess_algorithm::fen;
+use chess_algorithm::pawn_structure::{evaluate_pawns, PawnEval, PawnHashTable, CHAIN, DOUBLED, ISOLATED, PASSED};
+use chess_algorithm::pst::PST_SCALE;
+use chess_algorithm::zobrist;
+
+fn scaled(score: TaperedScore) -> TaperedScore {
+    score * PST_SCALE
+}
+
+#[test]
+fn test_doubled_isolated_passed() {
+    let (board, _) = fen::parse("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1").unwrap();
+    let eval = evaluate_pawns(&DEFAULT_PARAMS, &board);
+
+    let expected = DOUBLED + ISOLATED * 2 + PASSED[1] + PASSED[2];
+    assert_eq!(eval.white, scaled(expected));
+    assert_eq!(eval.black, TaperedScore::default());
+}
+
+#[test]
+fn test_passed_pawn_needs_free_adjacent_files() {
+    let (blocked, _) = fen::parse("4k3/3p4/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+    let (free, _) = fen::parse("4k3/1p6/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(evaluate_pawns(&DEFAULT_PARAMS, &blocked).white, scaled(ISOLATED));
+    assert_eq!(evaluate_pawns(&DEFAULT_PARAMS, &free).white, scaled(ISOLATED + PASSED[4]));
+}
+
+#[test]
+fn test_chain_and_colors_are_symmetric() {
+    let (white, _) = fen::parse("4k3/8/8/8/8/4P3/3P4/4K3 w - - 0 1").unwrap();
+    let (black, _) = fen::parse("4k3/3p4/4p3/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(evaluate_pawns(&DEFAULT_PARAMS, &white).white, scaled(CHAIN + PASSED[1] + PASSED[2]));
+    assert_eq!(evaluate_pawns(&DEFAULT_PARAMS, &white).white, evaluate_pawns(&DEFAULT_PARAMS, &black).black);
+}
+
+#[test]
+fn test_pawn_hash_table() {
+    let (board, _) = fen::parse(fen::START_FEN).unwrap();
+    let (knight_moved, _) = fen::parse("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1").unwrap();
+    assert_eq!(zobrist::pawn_hash(&board), zobrist::pawn_hash(&knight_moved));
+
+    let table = PawnHashTable::new(16);
+    assert_eq!(table.probe(zobrist::pawn_hash(&board)), None);
+    assert_eq!(table.evaluate(&DEFAULT_PARAMS, &board), evaluate_pawns(&DEFAULT_PARAMS, &board));
+    assert_eq!(table.probe(zobrist::pawn_hash(&board)), Some(evaluate_pawns(&DEFAULT_PARAMS, &board)));
+}
+
+#[test]
+fn test_pawn_hash_table_keeps_large_scores() {
+    let table = PawnHashTable::new(16);
+    let eval = PawnEval { white: TaperedScore::new(40000, -70000), black: TaperedScore::new(-32769, 1 << 20) };
+    table.store(12345, &eval);
+    assert_eq!(table.probe(12345), Some(eval));
+    assert_eq!(table.probe(54321), None);
+}
@@ -0,0 +1,69 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color;
+use chess_algorithm::movement::Move;
+use chess_algorithm::point::Point;
+use chess_algorithm::polyglot::{decode_move, encode_move, polyglot_key, BookEntry, OpeningBook};
+
+fn key(fen: &str) -> u64 {
+    let (board, color) = fen::parse(fen).unwrap();
+    polyglot_key(&board, color)
+}
+
+fn start_entry(raw_move: &str, weight: u16) -> BookEntry {
+    let movement = Move::from_string(raw_move).unwrap();
+    BookEntry { key: polyglot_key(&ByteBoard::default(), Color::WHITE), raw_move: encode_move(&movement), weight, learn: 0 }
+}
+
+#[test]
+fn test_reference_keys() {
+    // Keys from the Polyglot format description.
+    assert_eq!(polyglot_key(&ByteBoard::default(), Color::WHITE), 0x463b96181691fc9c);
+    assert_eq!(key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"), 0x823c9b50fd114196);
+    assert_eq!(key("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"), 0x0756b94461c50fb0);
+    assert_eq!(key("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2"), 0x662fafb965db29d4);
+    assert_eq!(key("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3"), 0x652a607ca3f242c1);
+    assert_eq!(key("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4"), 0x00fdd303c946bdd9);
+}
+
+#[test]
+fn test_move_encoding() {
+    let movement = Move::from_string("E2E4").unwrap();
+    assert_eq!(encode_move(&movement), 0x031c);
+    assert_eq!(decode_move(0x031c), (movement.from, movement.to, 0));
+
+    // h7h8q
+    assert_eq!(decode_move(0x4dff), (Point::new(0, 6), Point::new(0, 7), 4));
+}
+
+#[test]
+fn test_write_and_read() {
+    let book = OpeningBook::new(vec![start_entry("D2D4", 10), start_entry("E2E4", 30), BookEntry { key: 1, raw_move: 0, weight: 1, learn: 7 }]);
+    let mut bytes = Vec::new();
+    book.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 48);
+
+    let loaded = OpeningBook::read(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.len(), 3);
+    assert_eq!(loaded.entries(1), book.entries(1));
+    let start = loaded.entries(polyglot_key(&ByteBoard::default(), Color::WHITE));
+    assert_eq!(start.iter().map(|e| e.weight).collect::<Vec<_>>(), vec![30, 10]);
+    assert!(OpeningBook::read(&mut [0u8; 20].as_ref()).is_err());
+}
+
+#[test]
+fn test_weighted_choice() {
+    let book = OpeningBook::new(vec![start_entry("E2E4", 3), start_entry("D2D4", 1), start_entry("C2C4", 0)]);
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    let controller = holder.controller(Color::WHITE);
+
+    let e4 = Move::from_string("E2E4").unwrap();
+    let d4 = Move::from_string("D2D4").unwrap();
+    let chosen: Vec<_> = (0..4).map(|random| book.choose(&controller, random).unwrap()).collect();
+    assert_eq!(chosen.iter().filter(|m| m.from == e4.from && m.to == e4.to).count(), 3);
+    assert_eq!(chosen.iter().filter(|m| m.from == d4.from && m.to == d4.to).count(), 1);
+
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    assert_eq!(book.choose(&holder.controller(Color::BLACK), 0), None);
+}
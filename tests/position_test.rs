@@ -0,0 +1,63 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Figure;
+use chess_algorithm::figure::Rank::{KING, PAWN, QUEEN, ROOK};
+use chess_algorithm::point::Point;
+use chess_algorithm::position::{validate, PositionBuilder, PositionError};
+
+fn kings() -> PositionBuilder {
+    PositionBuilder::new()
+        .with_figure(Point::new(3, 0), Figure::new(KING, WHITE, false))
+        .with_figure(Point::new(3, 7), Figure::new(KING, BLACK, false))
+}
+
+#[test]
+fn test_build() {
+    let (board, color) = kings()
+        .with_figure(Point::new(3, 1), Figure::new(PAWN, WHITE, false))
+        .with_turn(BLACK)
+        .build()
+        .unwrap();
+
+    assert_eq!(color, BLACK);
+    assert_eq!(*board.point(Point::new(3, 1)), Figure::new(PAWN, WHITE, false));
+    assert_eq!(validate(&ByteBoard::default(), WHITE), Ok(()));
+    assert_eq!(PositionBuilder::from_board(&ByteBoard::default()).build(), Ok((ByteBoard::default(), WHITE)));
+}
+
+#[test]
+fn test_illegal_setups() {
+    assert_eq!(PositionBuilder::new().build(), Err(PositionError::KINGS(WHITE, 0)));
+    let three_kings = kings().with_figure(Point::new(0, 7), Figure::new(KING, BLACK, false))
+        .with_figure(Point::new(7, 7), Figure::new(KING, BLACK, false));
+    assert_eq!(three_kings.build(), Err(PositionError::KINGS(BLACK, 3)));
+
+    let pawn = kings().with_figure(Point::new(5, 0), Figure::new(PAWN, WHITE, false));
+    assert_eq!(pawn.build(), Err(PositionError::PAWN(Point::new(5, 0))));
+
+    let outside = kings().with_figure(Point::new(8, 2), Figure::new(ROOK, WHITE, false));
+    assert_eq!(outside.build(), Err(PositionError::OUTSIDE(Point::new(8, 2))));
+
+    let crowded = (0..8).fold(kings(), |b, x| {
+        b.with_figure(Point::new(x, 2), Figure::new(PAWN, WHITE, false))
+            .with_figure(Point::new(x, 3), Figure::new(QUEEN, WHITE, false))
+    });
+    assert_eq!(crowded.build(), Err(PositionError::FIGURES(WHITE, 17)));
+}
+
+#[test]
+fn test_check() {
+    let rook = kings().with_figure(Point::new(3, 4), Figure::new(ROOK, WHITE, false));
+    assert_eq!(rook.clone().with_turn(BLACK).build().map(|(_, c)| c), Ok(BLACK));
+    assert_eq!(rook.build(), Err(PositionError::CHECK(BLACK)));
+}
+
+#[test]
+fn test_fen() {
+    assert_eq!(fen::parse(fen::START_FEN).map(|(_, c)| c), Ok(WHITE));
+    assert_eq!(fen::parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w"), Err(PositionError::FORMAT));
+    assert_eq!(fen::parse("4k3/8/8/8/8/8/8/8 w - - 0 1"), Err(PositionError::KINGS(WHITE, 0)));
+    assert_eq!(fen::parse("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"), Err(PositionError::PAWN(Point::new(7, 0))));
+    assert!(fen::parse_unchecked("4k3/8/8/8/8/8/8/8 w - - 0 1").is_ok());
+}
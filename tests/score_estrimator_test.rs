@@ -1,9 +1,18 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use chess_algorithm::board::ByteBoard;
 use chess_algorithm::board_controller::BoardDataHolder;
 use chess_algorithm::figure::Color::WHITE;
 use chess_algorithm::figure::Figure;
 use chess_algorithm::figure::Rank::PAWN;
-use chess_algorithm::score::min_max_simple;
+use chess_algorithm::board_controller::BoardController;
+use chess_algorithm::fen;
+use chess_algorithm::figure_list::FigurePointList;
+use chess_algorithm::figure::{W_INFINITY, W_KING, W_KNIGHT, W_PAWN, W_QUEEN};
+use chess_algorithm::movement::Move;
+use chess_algorithm::point::Point;
+use chess_algorithm::score::{alpha_betta, alpha_betta_with, aspiration_search, min_max_simple, simple_positional_fn, AlphaBetaSearch, Evaluator, MoveSearch, SearchContext};
 
 #[test]
 fn test_simple_min_max() {
@@ -13,4 +22,144 @@ fn test_simple_min_max() {
     let mut holder =  BoardDataHolder::new(&board);
     let mut controller = holder.controller(WHITE);
     min_max_simple(&mut controller, 4);
-}
\ No newline at end of file
+}
+
+const SEARCH_SUITE: [&str; 5] = [
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "rnbqkbnr/ppp2ppp/8/3pp3/4P3/3P4/PPP2PPP/RNBQKBNR b KQkq - 0 3",
+    "4k3/8/3q4/8/2N5/8/8/4K2R w - - 0 1",
+    "6k1/5ppp/8/8/8/8/r4PPP/1R4K1 w - - 0 1",
+];
+
+/// Alpha-beta without null windows, the reference for `alpha_betta` results.
+fn plain_alpha_betta(controller: &mut BoardController, depth: i32, mut alpha: i32, betta: i32) -> (i32, Option<Move>) {
+    if depth <= 0 {
+        return (SearchContext::default().evaluator.evaluate(controller), None);
+    }
+
+    let mut move_list = controller.friend_movies();
+    move_list.sort_by(controller.board(), simple_positional_fn);
+
+    let mut best_score = - W_INFINITY;
+    let mut best_move = move_list.iter().next().copied();
+    for movement in move_list.iter() {
+        let move_info = controller.make_move(movement);
+        controller.pass_move_to_enemy();
+        let cur_score = - plain_alpha_betta(controller, depth - 1, - betta, - alpha).0;
+        controller.pass_move_to_enemy();
+        controller.unmake_move(move_info);
+
+        if cur_score > best_score {
+            best_score = cur_score;
+            best_move = Some(*movement);
+        }
+        if best_score > alpha { alpha = best_score }
+        if alpha >= betta { break }
+    }
+    (best_score, best_move)
+}
+
+#[test]
+fn test_pvs_matches_plain_alpha_betta() {
+    for fen in SEARCH_SUITE.iter() {
+        let (board, color) = fen::parse(fen).unwrap();
+        let mut holder = BoardDataHolder::new(&board);
+
+        let pvs = alpha_betta(&mut holder.controller(color), 4, - W_INFINITY, W_INFINITY);
+        let plain = plain_alpha_betta(&mut holder.controller(color), 4, - W_INFINITY, W_INFINITY);
+
+        assert_eq!(pvs, plain, "{}", fen);
+    }
+}
+
+#[test]
+fn test_aspiration_search_matches_full_window() {
+    for fen in SEARCH_SUITE.iter() {
+        let (board, color) = fen::parse(fen).unwrap();
+        let mut holder = BoardDataHolder::new(&board);
+
+        let full_window = alpha_betta(&mut holder.controller(color), 4, - W_INFINITY, W_INFINITY);
+        let narrow = aspiration_search(&SearchContext::default(), &mut holder.controller(color), 4, full_window.0 + 3 * W_PAWN, W_PAWN / 10);
+
+        assert_eq!(narrow, full_window, "{}", fen);
+    }
+}
+
+#[test]
+fn test_alpha_beta_search_finds_hanging_queen() {
+    let (board, color) = fen::parse(SEARCH_SUITE[3]).unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let (_, best_move) = AlphaBetaSearch::default().find_best_move(&mut holder.controller(color), 4);
+    assert_eq!(best_move.unwrap().to, Point::from_string("D6").unwrap());
+}
+
+const MATE_IN_ONE: [(&str, &str); 2] = [
+    ("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "A8"),
+    ("k7/8/1K6/8/8/8/8/7R w - - 0 1", "H8"),
+];
+
+#[test]
+fn test_check_extension_finds_mate() {
+    for (fen, mate_square) in MATE_IN_ONE.iter() {
+        let (board, color) = fen::parse(fen).unwrap();
+        let mut holder = BoardDataHolder::new(&board);
+
+        let (plain_score, _) = alpha_betta(&mut holder.controller(color), 2, - W_INFINITY, W_INFINITY);
+        assert!(plain_score < W_QUEEN, "{}", fen);
+
+        let context = SearchContext { max_extension: 2, ..SearchContext::default() };
+        let (score, best_move) = alpha_betta_with(&context, &mut holder.controller(color), 2, - W_INFINITY, W_INFINITY);
+        assert!(score > W_KING / 2, "{}", fen);
+        assert_eq!(best_move.unwrap().to, Point::from_string(mate_square).unwrap(), "{}", fen);
+    }
+}
+
+#[test]
+fn test_single_legal_reply_extension_finds_mate() {
+    let (board, color) = fen::parse("5r1k/7p/6R1/8/3B4/8/P7/KN6 b - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+
+    let mut controller = holder.controller(color);
+    assert!(controller.is_in_check());
+    assert_eq!(controller.legal_movies().len(), 1);
+
+    let (plain_score, plain_move) = alpha_betta(&mut holder.controller(color), 3, - W_INFINITY, W_INFINITY);
+    assert!(plain_score > - W_KING / 2);
+    assert_eq!(plain_move.unwrap().to, Point::from_string("F6").unwrap());
+
+    let context = SearchContext { max_extension: 1, ..SearchContext::default() };
+    let (score, _) = alpha_betta_with(&context, &mut holder.controller(color), 3, - W_INFINITY, W_INFINITY);
+    assert!(score < - W_KING / 2);
+}
+
+/// Material only evaluation counting its calls, written against the public API only.
+#[derive(Default)]
+struct CountingMaterialEvaluator {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Evaluator for CountingMaterialEvaluator {
+    fn evaluate(&self, controller: &BoardController) -> i32 {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let side_score = |list: &FigurePointList| -> i32 {
+            list.iter().map(|p| controller.board().point(p).weight()).sum()
+        };
+        side_score(controller.friend_list()) - side_score(controller.enemy_list())
+    }
+}
+
+#[test]
+fn test_custom_evaluator() {
+    let (board, color) = fen::parse(SEARCH_SUITE[3]).unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+
+    let evaluator = CountingMaterialEvaluator::default();
+    let calls = evaluator.calls.clone();
+    let mut controller = holder.controller(color);
+    let (score, best_move) = AlphaBetaSearch::with_evaluator(evaluator).find_best_move(&mut controller, 3);
+
+    assert_eq!(best_move.unwrap().to, Point::from_string("D6").unwrap());
+    assert!(score >= W_QUEEN - W_KNIGHT);
+    assert_eq!(calls.load(Ordering::Relaxed), controller.position_counter as usize);
+}
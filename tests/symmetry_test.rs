@@ -0,0 +1,85 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::evaluation::TaperedEvaluator;
+use chess_algorithm::fen;
+use chess_algorithm::figure::Color;
+use chess_algorithm::nnue::{Network, NnueEvaluator};
+use chess_algorithm::score::{Evaluator, SimpleEvaluator};
+
+const CORPUS: [&str; 8] = [
+    fen::START_FEN,
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "r1bq1rk1/pp3ppp/2n1pn2/3p4/1bPP4/2N1PN2/PP1B1PPP/R2QKB1R b KQ - 0 8",
+    "2r3k1/pp3pp1/4p2p/3pP3/1q1P4/1P3Q1P/P4PP1/2R3K1 w - - 0 25",
+    "8/5pk1/6p1/2P5/1P6/6PK/8/8 b - - 0 40",
+    "4k3/1P4p1/8/3pP3/2B5/8/6p1/4K2R w - - 0 1",
+    "r3k2r/1b2qppp/p1n1pn2/1pb5/4P3/P1N1BN2/1PQ1BPPP/R4RK1 w - - 4 13",
+    "8/8/3k4/8/2N5/4B3/8/4K3 b - - 0 60",
+];
+
+fn corpus() -> Vec<(ByteBoard, Color)> {
+    CORPUS.iter().map(|fen| fen::parse(fen).unwrap()).collect()
+}
+
+fn evaluators() -> Vec<(&'static str, Box<dyn Evaluator>)> {
+    vec![
+        ("simple", Box::new(SimpleEvaluator)),
+        ("tapered", Box::new(TaperedEvaluator::default())),
+        ("nnue", Box::new(NnueEvaluator::new(Network::random(16, 5)))),
+    ]
+}
+
+fn evaluate(evaluator: &dyn Evaluator, board: &ByteBoard, color: Color) -> i32 {
+    let mut holder = BoardDataHolder::new(board);
+    evaluator.evaluate(&holder.controller(color))
+}
+
+#[test]
+fn test_board_transformations() {
+    for (board, _) in corpus() {
+        assert_eq!(board.color_flipped().color_flipped(), board);
+        assert_eq!(board.mirrored().mirrored(), board);
+        assert_eq!(board.mirrored().color_flipped(), board.color_flipped().mirrored());
+    }
+    assert_eq!(ByteBoard::default().color_flipped(), ByteBoard::default());
+
+    let (board, _) = fen::parse("4k3/8/8/8/8/8/1N6/4K3 w - - 0 1").unwrap();
+    let (flipped, _) = fen::parse("4k3/1n6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let (mirrored, _) = fen::parse("3k4/8/8/8/8/8/6N1/3K4 w - - 0 1").unwrap();
+    assert_eq!(board.color_flipped(), flipped);
+    assert_eq!(board.mirrored(), mirrored);
+}
+
+#[test]
+fn test_color_flip_symmetry() {
+    for (name, evaluator) in evaluators() {
+        for (i, (board, color)) in corpus().iter().enumerate() {
+            for color in [*color, color.invert()] {
+                assert_eq!(
+                    evaluate(evaluator.as_ref(), board, color),
+                    evaluate(evaluator.as_ref(), &board.color_flipped(), color.invert()),
+                    "{} evaluator, position {}, {:?} to move", name, i, color
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_make_unmake_keeps_evaluation() {
+    for (name, evaluator) in evaluators() {
+        for (i, (board, color)) in corpus().iter().enumerate() {
+            let mut holder = BoardDataHolder::new(board);
+            let mut controller = holder.controller(*color);
+            evaluator.attach(&mut controller);
+            let score = evaluator.evaluate(&controller);
+
+            for movement in controller.friend_movies().iter() {
+                let info = controller.make_move(movement);
+                controller.unmake_move(info);
+                assert_eq!(evaluator.evaluate(&controller), score, "{} evaluator, position {}, move {}", name, i, movement);
+                assert_eq!(controller.board(), board);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::{Arc, LazyLock};
+
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::fen;
+use chess_algorithm::score::{iterative_deepening, AlphaBetaSearch, MoveSearch, SearchContext};
+use chess_algorithm::tablebase::{TbEntry, Tablebase, Wdl, TB_WIN};
+
+static TABLEBASE: LazyLock<Arc<Tablebase>> = LazyLock::new(|| {
+    let mut tablebase = Tablebase::new();
+    tablebase.generate("KPK").unwrap();
+    Arc::new(tablebase)
+});
+
+fn probe(tablebase: &Tablebase, fen: &str) -> Option<TbEntry> {
+    let (board, color) = fen::parse(fen).unwrap();
+    tablebase.probe(&board, color)
+}
+
+#[test]
+fn test_generation() {
+    assert_eq!(TABLEBASE.signatures(), vec!["KPK", "KQK"]);
+    // Known longest mates: ten moves in KQK and 28 in KPK, plies counted for the losing side to move.
+    assert_eq!(TABLEBASE.table("KQK").unwrap().longest_mate(), 20);
+    assert_eq!(TABLEBASE.table("KPK").unwrap().longest_mate(), 56);
+    assert!(Tablebase::new().generate("KQRKR").is_err());
+    assert!(Tablebase::new().generate("QK").is_err());
+}
+
+#[test]
+fn test_probe() {
+    let tablebase = &**TABLEBASE;
+    assert_eq!(probe(tablebase, "4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"), Some(TbEntry { wdl: Wdl::DRAW, dtm: 0 }));
+    assert_eq!(probe(tablebase, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap().wdl, Wdl::WIN);
+    assert_eq!(probe(tablebase, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").unwrap().wdl, Wdl::LOSS);
+    assert_eq!(probe(tablebase, "k7/8/K7/P7/8/8/8/8 w - - 0 1").unwrap().wdl, Wdl::DRAW);
+
+    // Black pawns are probed through the color flipped placement.
+    assert_eq!(probe(tablebase, "8/8/8/8/4p3/4k3/8/4K3 b - - 0 1").unwrap().wdl, Wdl::WIN);
+    assert_eq!(probe(tablebase, "8/8/8/8/8/8/3Kp3/7k w - - 0 1").unwrap().wdl, Wdl::DRAW);
+
+    // Black in check with white to move.
+    for fen in ["8/8/8/8/8/8/8/KQ5k w - - 0 1", "8/8/8/8/8/8/8/KR5k w - - 0 1"] {
+        let (board, color) = fen::parse_unchecked(fen).unwrap();
+        assert_eq!(tablebase.probe(&board, color), None);
+    }
+    assert_eq!(probe(tablebase, "8/8/8/8/8/8/8/K6k w - - 0 1"), Some(TbEntry { wdl: Wdl::DRAW, dtm: 0 }));
+}
+
+#[test]
+fn test_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("tablebase_test_{}", std::process::id()));
+    TABLEBASE.save_dir(&dir).unwrap();
+    let loaded = Tablebase::load_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.signatures(), TABLEBASE.signatures());
+    for fen in ["4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", "k7/8/2K5/8/8/8/8/7Q b - - 0 1"] {
+        assert_eq!(probe(&loaded, fen), probe(&TABLEBASE, fen));
+    }
+}
+
+#[test]
+fn test_search_probes_tables() {
+    let (board, color) = fen::parse("k7/4Q3/1K6/8/8/8/8/8 w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let search = AlphaBetaSearch { tablebase: Some(TABLEBASE.clone()), ..AlphaBetaSearch::default() };
+    let (score, movement) = search.find_best_move(&mut holder.controller(color), 3);
+    assert_eq!(score, TB_WIN);
+
+    let mut controller = holder.controller(color);
+    controller.make_move(&movement.unwrap());
+    controller.pass_move_to_enemy();
+    assert!(controller.is_in_check());
+    assert_eq!(controller.legal_movies().len(), 0);
+
+    let (board, color) = fen::parse("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap();
+    let mut holder = BoardDataHolder::new(&board);
+    let context = SearchContext { tablebase: Some(&**TABLEBASE), ..SearchContext::default() };
+    let (score, _) = iterative_deepening(&context, &mut holder.controller(color), 2, 0);
+    let expected = probe(&TABLEBASE, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap().score();
+    // The best child is a loss one ply shorter than the position's own distance to mate.
+    assert_eq!(score, expected + 1);
+}
@@ -0,0 +1,32 @@
+use chess_algorithm::movement::{Move, MoveType};
+use chess_algorithm::point::Point;
+use chess_algorithm::transposition::{Bound, TranspositionTable, TtEntry};
+
+fn entry(depth: i32, score: i32) -> TtEntry {
+    TtEntry {
+        depth,
+        score,
+        bound: Bound::LOWER,
+        best_move: Some(Move::new(Point::new(1, 6), Point::new(1, 7), MoveType::TRANSFORM)),
+    }
+}
+
+#[test]
+fn test_store_and_probe() {
+    let tt = TranspositionTable::new(1);
+    tt.store(0xDEAD_BEEF, entry(3, -1234));
+
+    assert_eq!(tt.probe(0xDEAD_BEEF), Some(entry(3, -1234)));
+    assert_eq!(tt.probe(0xDEAD_BEEF + (tt.len() as u64)), None);
+}
+
+#[test]
+fn test_deeper_entry_is_kept() {
+    let tt = TranspositionTable::new(1);
+    tt.store(42, entry(5, 100));
+    tt.store(42, entry(2, 200));
+    assert_eq!(tt.probe(42), Some(entry(5, 100)));
+
+    tt.clear();
+    assert_eq!(tt.probe(42), None);
+}
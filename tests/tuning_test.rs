@@ -0,0 +1,38 @@
+use chess_algorithm::eval_params::EvalParams;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::figure::Rank::{BISHOP, KNIGHT};
+use chess_algorithm::tuning::{parse_position, TexelTuner};
+
+#[test]
+fn test_parse_position() {
+    let position = parse_position("4k3/8/8/8/8/8/8/1N2K3 b - - 0 1; 1-0").unwrap();
+    assert_eq!(position.color, BLACK);
+    assert_eq!(position.result, 1.0);
+
+    let position = parse_position("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1 [0.5]").unwrap();
+    assert_eq!(position.color, WHITE);
+    assert_eq!(position.result, 0.5);
+
+    assert!(parse_position("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").is_err());
+    assert!(parse_position("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1; 2-0").is_err());
+}
+
+#[test]
+fn test_tuning_lowers_error() {
+    let positions: Vec<_> = [
+        "4k3/4p3/8/8/8/8/4P3/1N2K3 w - - 0 1; 1/2-1/2",
+        "4k3/4p3/8/8/8/8/4P3/1B2K3 w - - 0 1; 1/2-1/2",
+        "4k3/8/8/8/8/8/PPP5/4K3 w - - 0 1; 1-0",
+        "4k3/ppp5/8/8/8/8/8/4K3 w - - 0 1; 0-1",
+    ].iter().map(|line| parse_position(line).unwrap()).collect();
+
+    let mut tuner = TexelTuner::new(&positions);
+    let params = EvalParams::default();
+    tuner.fit_k(&params);
+    let error = tuner.error(&params);
+
+    let tuned = tuner.tune(&params, 1);
+    assert!(tuner.error(&tuned) < error);
+    assert!(tuned.piece_values[KNIGHT as usize] < params.piece_values[KNIGHT as usize]);
+    assert!(tuned.piece_values[BISHOP as usize] < params.piece_values[BISHOP as usize]);
+}
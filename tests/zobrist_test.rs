@@ -0,0 +1,26 @@
+use chess_algorithm::board::ByteBoard;
+use chess_algorithm::board_controller::BoardDataHolder;
+use chess_algorithm::figure::Color::{BLACK, WHITE};
+use chess_algorithm::movement::{Move, MoveType};
+use chess_algorithm::point::Point;
+use chess_algorithm::zobrist;
+
+#[test]
+fn test_hash_depends_on_side() {
+    let board = ByteBoard::default();
+    assert_eq!(zobrist::hash(&board, WHITE), zobrist::hash(&ByteBoard::default(), WHITE));
+    assert_ne!(zobrist::hash(&board, WHITE), zobrist::hash(&board, BLACK));
+}
+
+#[test]
+fn test_hash_restored_after_unmake_move() {
+    let mut holder = BoardDataHolder::new(&ByteBoard::default());
+    let before = zobrist::hash(&holder.board, WHITE);
+
+    let movement = Move::new(Point::new(3, 1), Point::new(3, 3), MoveType::SIMPLE);
+    let info = holder.controller(WHITE).make_move(&movement);
+    assert_ne!(zobrist::hash(&holder.board, WHITE), before);
+
+    holder.controller(WHITE).unmake_move(info);
+    assert_eq!(zobrist::hash(&holder.board, WHITE), before);
+}